@@ -1,15 +1,16 @@
 use std::env::args;
-use std::io::BufWriter;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::process;
 
-use conllu::io::{Reader, WriteSentence, Writer};
 use getopts::Options;
-use ohnomore::transform::lemmatization::{
-    AddReflexiveTag, AddSeparatedVerbPrefix, FormAsLemma, MarkVerbPrefix, RestoreCase,
-};
-use ohnomore::transform::misc::{
-    SimplifyArticleLemma, SimplifyPIAT, SimplifyPIDAT, SimplifyPIS, SimplifyPossesivePronounLemma,
-};
-use ohnomore::transform::Transforms;
+use ohnomore::data::{DataBundle, TuebaRelease};
+use ohnomore::transform::compound::{AddCompoundSplit, CompoundSplitter};
+use ohnomore::transform::lemmatization::{AddContextualReflexiveTag, ReflexiveConfidence};
+use ohnomore::transform::pipeline;
+use ohnomore::transform::{MiscTransform, MiscTransforms, Transforms};
+use ohnomore_utils::format::{self, Format};
+use ohnomore_utils::vrt::VrtColumns;
 use stdinout::{Input, OrExit, Output};
 
 fn print_usage(program: &str, opts: Options) {
@@ -22,8 +23,53 @@ fn main() {
     let program = args[0].clone();
 
     let mut opts = Options::new();
+    opts.optopt(
+        "c",
+        "compounds",
+        "add compound analyses of nouns to MISC, using a noun lexicon",
+        "LEXICON",
+    );
+    opts.optflag(
+        "",
+        "combine-particles",
+        "combine separated verb particles with attached prefixes",
+    );
+    opts.optopt(
+        "f",
+        "format",
+        "corpus format: conllu (default), export, jsonl or vrt",
+        "FORMAT",
+    );
     opts.optflag("h", "help", "print this help menu");
-
+    opts.optflag(
+        "l",
+        "linear-particles",
+        "attach separable verb particles by word order, for input without dependency relations",
+    );
+    opts.optopt(
+        "m",
+        "misc",
+        "store transformed lemmas in the MISC feature KEY, retaining the original lemmas",
+        "KEY",
+    );
+    opts.optopt(
+        "r",
+        "reflexives",
+        "mark reflexive personal pronouns (PPER) with high or low confidence",
+        "CONFIDENCE",
+    );
+    opts.optopt(
+        "",
+        "tueba-release",
+        "TüBa-D/Z release of the bundled lexicons: 10 or 11 (default)",
+        "RELEASE",
+    );
+    opts.optopt(
+        "",
+        "vrt-columns",
+        "comma-separated VRT columns (default: form,pos,lemma), see the README",
+        "COLUMNS",
+    );
     let matches = opts
         .parse(&args[1..])
         .or_exit("Cannot parse command-line options", 1);
@@ -38,34 +84,92 @@ fn main() {
         return;
     }
 
-    let transforms = Transforms(vec![
-        Box::new(FormAsLemma),
-        Box::new(RestoreCase),
-        Box::new(AddReflexiveTag),
-        Box::new(AddSeparatedVerbPrefix::new(true)),
-        Box::new(MarkVerbPrefix::new()),
-        Box::new(SimplifyArticleLemma),
-        Box::new(SimplifyPossesivePronounLemma),
-        Box::new(SimplifyPIS),
-        Box::new(SimplifyPIDAT),
-        Box::new(SimplifyPIAT),
-    ]);
-
-    let input = Input::from(matches.free.get(0));
-    let reader = Reader::new(input.buf_read().or_exit("Cannot read corpus", 1));
+    let reflexive_confidence = matches
+        .opt_str("r")
+        .map(|confidence| match confidence.as_str() {
+            "high" => ReflexiveConfidence::High,
+            "low" => ReflexiveConfidence::Low,
+            _ => {
+                eprintln!("Unknown reflexive confidence: {}", confidence);
+                process::exit(1);
+            }
+        });
 
-    let output = Output::from(matches.free.get(1));
-    let mut writer = Writer::new(BufWriter::new(
-        output.write().or_exit("Cannot open file for writing", 1),
-    ));
+    let release = matches
+        .opt_str("tueba-release")
+        .map(|release| release.parse::<TuebaRelease>())
+        .transpose()
+        .or_exit("Cannot parse TüBa-D/Z release", 1)
+        .unwrap_or_default();
+    let data = DataBundle::for_release(release);
 
-    for sentence in reader {
-        let mut sentence = sentence.or_exit("Cannot read sentence", 1);
+    if matches.opt_present("l") && matches.opt_present("combine-particles") {
+        eprintln!("--combine-particles cannot be used with --linear-particles");
+        process::exit(1);
+    }
 
-        transforms.transform(&mut sentence);
+    let mut transforms = if matches.opt_present("l") {
+        pipeline::lemmatization_without_parse_with_data(&data)
+    } else if matches.opt_present("combine-particles") {
+        pipeline::lemmatization_combining_prefixes_with_data(&data)
+    } else {
+        pipeline::lemmatization_with_data(&data)
+    };
 
-        writer
-            .write_sentence(&sentence)
-            .or_exit("Cannot write sentence", 1);
+    if let Some(confidence) = reflexive_confidence {
+        transforms.push(Box::new(AddContextualReflexiveTag::with_data(
+            confidence, &data,
+        )));
     }
+
+    let transforms = Transforms::new(transforms);
+
+    let mut misc_transforms: Vec<Box<dyn MiscTransform>> = Vec::new();
+    if let Some(lexicon_path) = matches.opt_str("c") {
+        let f = File::open(lexicon_path).or_exit("Cannot open compound lexicon", 1);
+        let splitter = CompoundSplitter::read_lexicon(BufReader::new(f))
+            .or_exit("Cannot read compound lexicon", 1);
+        misc_transforms.push(Box::new(AddCompoundSplit::new(splitter)));
+    }
+
+    let misc_transforms = MiscTransforms::new(misc_transforms);
+
+    let misc_key = matches.opt_str("m");
+
+    let mut corpus_format = matches
+        .opt_str("f")
+        .map(|format| format.parse::<Format>())
+        .transpose()
+        .or_exit("Cannot parse corpus format", 1)
+        .unwrap_or(Format::Conllu);
+
+    if let Some(columns) = matches.opt_str("vrt-columns") {
+        let columns = columns
+            .parse::<VrtColumns>()
+            .or_exit("Cannot parse VRT columns", 1);
+        corpus_format = corpus_format
+            .with_vrt_columns(columns)
+            .or_exit("Cannot use VRT columns", 1);
+    }
+
+    if (misc_key.is_some() || !misc_transforms.transforms().is_empty()) && !corpus_format.has_misc()
+    {
+        eprintln!("The corpus format cannot store MISC features");
+        process::exit(1);
+    }
+
+    let input = Input::from(matches.free.first());
+    let read = input.buf_read().or_exit("Cannot read corpus", 1);
+
+    let output = Output::from(matches.free.get(1));
+    let write = BufWriter::new(output.write().or_exit("Cannot open file for writing", 1));
+
+    format::process(corpus_format, read, write, |graph| {
+        match misc_key {
+            Some(ref key) => transforms.transform_to_misc(graph, key),
+            None => transforms.transform(graph),
+        }
+        misc_transforms.transform(graph);
+    })
+    .or_exit("Cannot process corpus", 1);
 }
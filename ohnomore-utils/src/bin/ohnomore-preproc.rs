@@ -1,14 +1,12 @@
 use std::env::args;
 use std::io::BufWriter;
+use std::process;
 
-use conllu::io::{Reader, WriteSentence, Writer};
 use getopts::Options;
-use ohnomore::transform::delemmatization::{
-    RemoveAlternatives, RemoveReflexiveTag, RemoveSepVerbPrefix, RemoveTruncMarker,
-};
-use ohnomore::transform::lemmatization::FormAsLemma;
-use ohnomore::transform::misc::{SimplifyArticleLemma, SimplifyPossesivePronounLemma};
+use ohnomore::transform::pipeline;
 use ohnomore::transform::Transforms;
+use ohnomore_utils::format::{self, Format};
+use ohnomore_utils::vrt::VrtColumns;
 use stdinout::{Input, OrExit, Output};
 
 fn print_usage(program: &str, opts: Options) {
@@ -21,7 +19,30 @@ fn main() {
     let program = args[0].clone();
 
     let mut opts = Options::new();
+    opts.optflag(
+        "a",
+        "attached-prefixes",
+        "retain separable verb prefixes that are attached to the form",
+    );
+    opts.optopt(
+        "f",
+        "format",
+        "corpus format: conllu (default), export, jsonl or vrt",
+        "FORMAT",
+    );
     opts.optflag("h", "help", "print this help menu");
+    opts.optopt(
+        "m",
+        "misc",
+        "store transformed lemmas in the MISC feature KEY, retaining the original lemmas",
+        "KEY",
+    );
+    opts.optopt(
+        "",
+        "vrt-columns",
+        "comma-separated VRT columns (default: form,pos,lemma), see the README",
+        "COLUMNS",
+    );
     let matches = opts
         .parse(&args[1..])
         .or_exit("Cannot parse command-line options", 1);
@@ -36,31 +57,44 @@ fn main() {
         return;
     }
 
-    let transforms = Transforms(vec![
-        Box::new(RemoveAlternatives),
-        Box::new(RemoveReflexiveTag),
-        Box::new(RemoveSepVerbPrefix),
-        Box::new(RemoveTruncMarker),
-        Box::new(SimplifyArticleLemma),
-        Box::new(SimplifyPossesivePronounLemma),
-        Box::new(FormAsLemma),
-    ]);
+    let transforms = if matches.opt_present("a") {
+        Transforms::new(pipeline::delemmatization_retaining_attached_prefixes())
+    } else {
+        Transforms::new(pipeline::delemmatization())
+    };
 
-    let input = Input::from(matches.free.get(0));
-    let reader = Reader::new(input.buf_read().or_exit("Cannot read corpus", 1));
+    let misc_key = matches.opt_str("m");
 
-    let output = Output::from(matches.free.get(1));
-    let mut writer = Writer::new(BufWriter::new(
-        output.write().or_exit("Cannot open file for writing", 1),
-    ));
-
-    for sentence in reader {
-        let mut sentence = sentence.or_exit("Cannot read sentence", 1);
+    let mut corpus_format = matches
+        .opt_str("f")
+        .map(|format| format.parse::<Format>())
+        .transpose()
+        .or_exit("Cannot parse corpus format", 1)
+        .unwrap_or(Format::Conllu);
 
-        transforms.transform(&mut sentence);
+    if let Some(columns) = matches.opt_str("vrt-columns") {
+        let columns = columns
+            .parse::<VrtColumns>()
+            .or_exit("Cannot parse VRT columns", 1);
+        corpus_format = corpus_format
+            .with_vrt_columns(columns)
+            .or_exit("Cannot use VRT columns", 1);
+    }
 
-        writer
-            .write_sentence(&sentence)
-            .or_exit("Cannot write sentence", 1);
+    if misc_key.is_some() && !corpus_format.has_misc() {
+        eprintln!("The corpus format cannot store MISC features");
+        process::exit(1);
     }
+
+    let input = Input::from(matches.free.first());
+    let read = input.buf_read().or_exit("Cannot read corpus", 1);
+
+    let output = Output::from(matches.free.get(1));
+    let write = BufWriter::new(output.write().or_exit("Cannot open file for writing", 1));
+
+    format::process(corpus_format, read, write, |graph| match misc_key {
+        Some(ref key) => transforms.transform_to_misc(graph, key),
+        None => transforms.transform(graph),
+    })
+    .or_exit("Cannot process corpus", 1);
 }
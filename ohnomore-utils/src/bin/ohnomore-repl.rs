@@ -0,0 +1,99 @@
+use std::env::args;
+use std::io::{self, BufRead};
+use std::process;
+
+use getopts::Options;
+use ohnomore::transform::pipeline::{
+    self, NamedTransform, DELEMMATIZATION_TRANSFORMS, LEMMATIZATION_TRANSFORMS,
+};
+use ohnomore::transform::test_cases::TestGraph;
+use ohnomore::transform::Transform;
+use stdinout::OrExit;
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options]", program);
+    print!("{}", opts.usage(&brief));
+    println!();
+    println!("Reads tokens from the standard input, one per line:");
+    println!();
+    println!("  form lemma upos xpos [rel head_form head_lemma head_upos head_xpos]");
+    println!("    [rel dep_form dep_lemma dep_upos dep_xpos]*");
+    println!();
+    println!("A single - in place of the head marks a token without a head:");
+    println!();
+    println!("  _ zeichnen _ VVFIN - AVZ ab _ _ PTKVZ");
+}
+
+/// Apply the transformations of a pipeline one by one, printing the
+/// lemma of the token after each transformation.
+fn print_steps(
+    pipeline: &str,
+    names: &[NamedTransform],
+    transforms: &[Box<dyn Transform>],
+    graph: &TestGraph,
+) {
+    // Each pipeline starts from the lemmas of the input.
+    let mut graph = graph.clone();
+
+    println!("{}:", pipeline);
+
+    for ((name, _), transform) in names.iter().zip(transforms) {
+        for idx in 1..graph.graph().len() {
+            let lemma = transform.transform(graph.graph(), idx).into_owned();
+            graph.graph_mut().token_mut(idx).set_lemma(Some(lemma));
+        }
+
+        println!("  {:32}{}", name, graph.token().lemma());
+    }
+}
+
+fn main() {
+    let args: Vec<String> = args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    let matches = opts
+        .parse(&args[1..])
+        .or_exit("Cannot parse command-line options", 1);
+
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return;
+    }
+
+    if !matches.free.is_empty() {
+        print_usage(&program, opts);
+        process::exit(1);
+    }
+
+    let delemmatization = pipeline::delemmatization();
+    let lemmatization = pipeline::lemmatization();
+
+    let stdin = io::stdin();
+    for (line_idx, line) in stdin.lock().lines().enumerate() {
+        let line = line.or_exit("Cannot read line", 1);
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let graph = match TestGraph::parse(line, line_idx + 1) {
+            Ok(graph) => graph,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+
+        print_steps(
+            "preproc",
+            DELEMMATIZATION_TRANSFORMS,
+            &delemmatization,
+            &graph,
+        );
+        print_steps("postproc", LEMMATIZATION_TRANSFORMS, &lemmatization, &graph);
+        println!();
+    }
+}
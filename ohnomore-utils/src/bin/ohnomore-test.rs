@@ -0,0 +1,89 @@
+use std::env::args;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+
+use getopts::Options;
+use ohnomore::transform::pipeline::{transform_by_name, transform_names};
+use ohnomore::transform::test_cases::read_test_cases;
+use stdinout::OrExit;
+
+fn print_usage(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options] TRANSFORM TEST_FILE...", program);
+    print!("{}", opts.usage(&brief));
+}
+
+fn main() {
+    let args: Vec<String> = args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optflag("l", "list", "list the available transformations");
+    let matches = opts
+        .parse(&args[1..])
+        .or_exit("Cannot parse command-line options", 1);
+
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return;
+    }
+
+    if matches.opt_present("l") {
+        for name in transform_names() {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    if matches.free.len() < 2 {
+        print_usage(&program, opts);
+        process::exit(1);
+    }
+
+    let transform_name = &matches.free[0];
+    let transform = match transform_by_name(transform_name) {
+        Some(transform) => transform,
+        None => {
+            eprintln!("Unknown transformation: {}", transform_name);
+            process::exit(1);
+        }
+    };
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for path in &matches.free[1..] {
+        let f = File::open(path).or_exit(format!("Cannot open {}", path), 1);
+        let test_cases =
+            read_test_cases(BufReader::new(f)).or_exit(format!("Cannot read {}", path), 1);
+
+        for test_case in test_cases {
+            let transformed = test_case.transform(transform.as_ref());
+            if transformed == test_case.correct() {
+                passed += 1;
+                continue;
+            }
+
+            failed += 1;
+
+            let token = test_case.token();
+            println!(
+                "{}:{}: {} {} {}: expected {}, got {}",
+                path,
+                test_case.line(),
+                token.form(),
+                token.lemma(),
+                token.xpos(),
+                test_case.correct(),
+                transformed
+            );
+        }
+    }
+
+    println!("{} passed, {} failed", passed, failed);
+
+    if failed != 0 {
+        process::exit(1);
+    }
+}
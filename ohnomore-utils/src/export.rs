@@ -0,0 +1,516 @@
+//! NEGRA/TüBa-D/Z export format sentences.
+//!
+//! TüBa-D/Z is distributed in version 4 of the export format. A sentence
+//! starts with a `#BOS` line and ends with an `#EOS` line. Each token is a
+//! line with the fields *form*, *lemma*, *tag*, *morphology*, *edge* and
+//! *parent*, optionally followed by secondary edges and a `%%` comment.
+//! Lines starting with `#5xx` are the phrasal nodes of the constituency
+//! tree, which use the same fields.
+//!
+//! The export format does not have dependency relations. To support
+//! separable verb particles, each particle (*PTKVZ*) is attached with the
+//! *AVZ* relation to the closest finite verb in the same clause. Other
+//! relations are not available.
+//!
+//! Sentences are written back in their original form: only the lines of
+//! tokens that were changed are reformatted. MISC features are added to
+//! the comment of a token. Lines outside sentences are preserved as well.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use ohnomore::transform::{DependencyGraph, Token, TokenMut};
+use thiserror::Error;
+
+use crate::stts::universal_tag;
+
+static SEPARABLE_PARTICLE_TAG: &str = "PTKVZ";
+
+static SEPARABLE_PARTICLE_RELATION: &str = "AVZ";
+
+static FINITE_VERB_TAGS: [&str; 4] = ["VAFIN", "VMFIN", "VVFIN", "VVIMP"];
+
+static CLAUSE_TAGS: [&str; 4] = ["SIMPX", "R-SIMPX", "P-SIMPX", "FKONJ"];
+
+static COMMENT_MARKER: &str = "%%";
+
+static EMPTY_FIELD: &str = "--";
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    IO(#[from] io::Error),
+
+    #[error("Invalid export data on line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+
+    #[error("Unsupported export format version on line {line}: {version}")]
+    UnsupportedVersion { line: usize, version: String },
+}
+
+/// A sentence in export format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportSentence {
+    preamble: Vec<String>,
+    bos: String,
+    tokens: Vec<ExportToken>,
+    nodes: Vec<String>,
+    eos: String,
+    particle_heads: Vec<Option<usize>>,
+}
+
+impl ExportSentence {
+    /// Get the tokens of the sentence.
+    pub fn tokens(&self) -> &[ExportToken] {
+        &self.tokens
+    }
+}
+
+impl DependencyGraph for ExportSentence {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(
+            self.particle_heads
+                .iter()
+                .enumerate()
+                .filter(move |(_, head)| **head == Some(idx))
+                .map(|(dependent, _)| (dependent + 1, SEPARABLE_PARTICLE_RELATION)),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        self.particle_heads[idx.checked_sub(1)?].map(|head| (head, SEPARABLE_PARTICLE_RELATION))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        assert!(idx != 0, "The root node was used as a token");
+        &self.tokens[idx - 1]
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        assert!(idx != 0, "The root node was used as a token");
+        &mut self.tokens[idx - 1]
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len() + 1
+    }
+}
+
+/// A token in export format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportToken {
+    line: String,
+    fields: Vec<String>,
+    comment: Option<String>,
+    upos: &'static str,
+    parent: usize,
+    changed: bool,
+}
+
+impl ExportToken {
+    /// Get the morphology field.
+    pub fn morph(&self) -> &str {
+        &self.fields[3]
+    }
+
+    /// Get the edge label.
+    pub fn edge(&self) -> &str {
+        &self.fields[4]
+    }
+
+    /// Get the parent node.
+    ///
+    /// The parent is *0* for tokens that are attached to the virtual root.
+    pub fn parent(&self) -> usize {
+        self.parent
+    }
+
+    fn write(&self, write: &mut dyn Write) -> io::Result<()> {
+        if !self.changed {
+            return writeln!(write, "{}", self.line);
+        }
+
+        write!(write, "{}", self.fields.join("\t"))?;
+        if let Some(ref comment) = self.comment {
+            write!(write, "\t{} {}", COMMENT_MARKER, comment)?;
+        }
+        writeln!(write)
+    }
+}
+
+impl Token for ExportToken {
+    fn form(&self) -> &str {
+        &self.fields[0]
+    }
+
+    fn lemma(&self) -> &str {
+        &self.fields[1]
+    }
+
+    fn upos(&self) -> &str {
+        self.upos
+    }
+
+    fn xpos(&self) -> &str {
+        &self.fields[2]
+    }
+}
+
+impl TokenMut for ExportToken {
+    fn set_lemma(&mut self, lemma: Option<String>) {
+        self.fields[1] = lemma.unwrap_or_else(|| EMPTY_FIELD.to_owned());
+        self.changed = true;
+    }
+
+    fn set_misc(&mut self, key: &str, value: String) {
+        let feature = format!("{}={}", key, value);
+        self.comment = Some(match self.comment.take() {
+            Some(comment) => format!("{} {}", comment, feature),
+            None => feature,
+        });
+        self.changed = true;
+    }
+}
+
+/// Split a line into its fields and comment.
+fn split_line(line: &str) -> (Vec<&str>, Option<&str>) {
+    let (data, comment) = match line.find(COMMENT_MARKER) {
+        Some(idx) => (
+            &line[..idx],
+            Some(line[idx + COMMENT_MARKER.len()..].trim()),
+        ),
+        None => (line, None),
+    };
+
+    (data.split_whitespace().collect(), comment)
+}
+
+fn parse_parent(field: &str, line: usize) -> Result<usize, ExportError> {
+    field.parse().map_err(|_| ExportError::Parse {
+        line,
+        reason: format!("invalid parent: {}", field),
+    })
+}
+
+fn parse_token(line: &str, line_number: usize) -> Result<ExportToken, ExportError> {
+    let (fields, comment) = split_line(line);
+    if fields.len() < 6 {
+        return Err(ExportError::Parse {
+            line: line_number,
+            reason: "token with fewer than six fields".to_owned(),
+        });
+    }
+
+    Ok(ExportToken {
+        line: line.to_owned(),
+        upos: universal_tag(fields[2], fields[1]),
+        parent: parse_parent(fields[5], line_number)?,
+        fields: fields.into_iter().map(ToOwned::to_owned).collect(),
+        comment: comment.map(ToOwned::to_owned),
+        changed: false,
+    })
+}
+
+/// Check whether a line is a phrasal node (`#500` and up).
+fn is_node(line: &str) -> bool {
+    match line.split_whitespace().next() {
+        Some(id) => {
+            id.len() > 3 && id.starts_with('#') && id[1..].chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Attach separable verb particles to the closest finite verb in the
+/// same clause.
+fn attach_particles(
+    tokens: &[ExportToken],
+    nodes: &HashMap<usize, (String, usize)>,
+) -> Vec<Option<usize>> {
+    // Find the closest clause that dominates a token.
+    let clause = |token: &ExportToken| {
+        let mut node = token.parent;
+
+        // Limit the number of steps, in case the tree contains a cycle.
+        for _ in 0..=nodes.len() {
+            let (tag, parent) = nodes.get(&node)?;
+            if CLAUSE_TAGS.contains(&tag.as_str()) {
+                return Some(node);
+            }
+            node = *parent;
+        }
+
+        None
+    };
+
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, token)| {
+            if token.xpos() != SEPARABLE_PARTICLE_TAG {
+                return None;
+            }
+
+            let particle_clause = clause(token);
+
+            tokens
+                .iter()
+                .enumerate()
+                .filter(|(_, verb)| FINITE_VERB_TAGS.contains(&verb.xpos()))
+                .filter(|(_, verb)| clause(verb) == particle_clause)
+                .min_by_key(|(verb_idx, _)| (idx.abs_diff(*verb_idx), *verb_idx > idx))
+                .map(|(verb_idx, _)| verb_idx + 1)
+        })
+        .collect()
+}
+
+/// Reader for sentences in export format.
+pub struct Reader<R> {
+    read: R,
+    line: usize,
+    trailer: Vec<String>,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(read: R) -> Self {
+        Reader {
+            read,
+            line: 0,
+            trailer: Vec::new(),
+        }
+    }
+
+    /// Get the lines after the last sentence.
+    ///
+    /// The lines are only available after all sentences were read.
+    pub fn trailer(&self) -> &[String] {
+        &self.trailer
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, ExportError> {
+        let mut line = String::new();
+        if self.read.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+
+        let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+        line.truncate(len);
+
+        Ok(Some(line))
+    }
+
+    /// Read a sentence, returning `None` at the end of the input.
+    pub fn read_sentence(&mut self) -> Result<Option<ExportSentence>, ExportError> {
+        let mut preamble = Vec::new();
+
+        let bos = loop {
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => {
+                    self.trailer = preamble;
+                    return Ok(None);
+                }
+            };
+
+            if line.starts_with("#BOS") {
+                break line;
+            }
+
+            if let Some(version) = line.strip_prefix("#FORMAT") {
+                let version = version.trim();
+                if version != "4" {
+                    return Err(ExportError::UnsupportedVersion {
+                        line: self.line,
+                        version: version.to_owned(),
+                    });
+                }
+            }
+
+            preamble.push(line);
+        };
+
+        let mut tokens = Vec::new();
+        let mut node_lines = Vec::new();
+        let mut nodes = HashMap::new();
+
+        let eos = loop {
+            let line = self.read_line()?.ok_or_else(|| ExportError::Parse {
+                line: self.line,
+                reason: "sentence without #EOS".to_owned(),
+            })?;
+
+            if line.starts_with("#EOS") {
+                break line;
+            }
+
+            if is_node(&line) {
+                let node = parse_token(&line, self.line)?;
+                let id = parse_parent(&node.fields[0][1..], self.line)?;
+                nodes.insert(id, (node.fields[2].clone(), node.parent));
+                node_lines.push(line);
+            } else {
+                tokens.push(parse_token(&line, self.line)?);
+            }
+        };
+
+        let particle_heads = attach_particles(&tokens, &nodes);
+
+        Ok(Some(ExportSentence {
+            preamble,
+            bos,
+            tokens,
+            nodes: node_lines,
+            eos,
+            particle_heads,
+        }))
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<ExportSentence, ExportError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_sentence().transpose()
+    }
+}
+
+/// Writer for sentences in export format.
+pub struct Writer<W> {
+    write: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(write: W) -> Self {
+        Writer { write }
+    }
+
+    /// Write a sentence, including the lines that preceded it.
+    pub fn write_sentence(&mut self, sentence: &ExportSentence) -> Result<(), ExportError> {
+        self.write_lines(&sentence.preamble)?;
+        writeln!(self.write, "{}", sentence.bos)?;
+        for token in &sentence.tokens {
+            token.write(&mut self.write)?;
+        }
+        self.write_lines(&sentence.nodes)?;
+        writeln!(self.write, "{}", sentence.eos)?;
+        Ok(())
+    }
+
+    /// Write lines verbatim, e.g. the trailer of a reader.
+    pub fn write_lines(&mut self, lines: &[String]) -> Result<(), ExportError> {
+        for line in lines {
+            writeln!(self.write, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ohnomore::transform::{pipeline, DependencyGraph, Transforms};
+
+    use super::{ExportError, Reader, Writer};
+
+    // Sie ruft den Mann, der kommt, an.
+    static SENTENCE: &str = "#FORMAT 4\n\
+#BOS 1 1 1070544990 0 %% HEADLINE\n\
+Sie\tsie\tPPER\tnsf3\tHD\t500\n\
+ruft\trufen\tVVFIN\t3sis\tHD\t501\n\
+den\td\tART\tasm\t-\t507\n\
+Mann\tMann\tNN\tasm\tHD\t507\n\
+,\t,\t$,\t--\t--\t0\n\
+der\td\tPRELS\tnsm\tHD\t502\n\
+kommt\tkommen\tVVFIN\t3sis\tHD\t503\n\
+,\t,\t$,\t--\t--\t0\n\
+an\tan\tPTKVZ\t--\tVPT\t513\t%% particle\n\
+.\t.\t$.\t--\t--\t0\n\
+#500\t--\tNX\t--\tON\t510\n\
+#501\t--\tVXFIN\t--\tHD\t511\n\
+#502\t--\tNX\t--\tON\t504\n\
+#503\t--\tVXFIN\t--\tHD\t505\n\
+#504\t--\tC\t--\t-\t506\n\
+#505\t--\tVC\t--\t-\t506\n\
+#506\t--\tR-SIMPX\t--\t-\t507\n\
+#507\t--\tNX\t--\tOA\t512\n\
+#510\t--\tVF\t--\t-\t514\n\
+#511\t--\tLK\t--\t-\t514\n\
+#512\t--\tMF\t--\t-\t514\n\
+#513\t--\tVC\t--\t-\t514\n\
+#514\t--\tSIMPX\t--\t--\t0\n\
+#EOS 1\n\
+#EOT\n";
+
+    #[test]
+    pub fn reads_sentences() {
+        let mut reader = Reader::new(Cursor::new(SENTENCE));
+        let sentence = reader.next().unwrap().unwrap();
+        assert!(reader.next().is_none());
+        assert_eq!(reader.trailer(), &["#EOT".to_owned()]);
+
+        assert_eq!(sentence.len(), 11);
+        assert_eq!(sentence.token(4).form(), "Mann");
+        assert_eq!(sentence.token(4).upos(), "NOUN");
+        assert_eq!(sentence.tokens()[3].morph(), "asm");
+        assert_eq!(sentence.tokens()[3].parent(), 507);
+
+        // The particle belongs to the main clause, not to the closer
+        // verb of the relative clause.
+        assert_eq!(sentence.dependents(2).collect::<Vec<_>>(), vec![(9, "AVZ")]);
+        assert_eq!(sentence.dependents(7).count(), 0);
+        assert_eq!(sentence.head(9), Some((2, "AVZ")));
+        assert_eq!(sentence.head(2), None);
+    }
+
+    #[test]
+    pub fn roundtrips_sentences() {
+        let mut reader = Reader::new(Cursor::new(SENTENCE));
+        let mut writer = Writer::new(Vec::new());
+        let transforms = Transforms::new(pipeline::lemmatization());
+
+        for sentence in &mut reader {
+            let mut sentence = sentence.unwrap();
+            transforms.transform(&mut sentence);
+            sentence.token_mut(9).set_misc("Particle", "yes".to_owned());
+            writer.write_sentence(&sentence).unwrap();
+        }
+        writer.write_lines(reader.trailer()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.write).unwrap(),
+            SENTENCE
+                .replace("\trufen\t", "\tan#rufen\t")
+                .replace("%% particle", "%% particle Particle=yes")
+        );
+    }
+
+    #[test]
+    pub fn rejects_invalid_sentences() {
+        for (data, line) in &[
+            ("#BOS 1\nSie\tsie\tPPER\n#EOS 1\n", 2),
+            ("#BOS 1\nSie\tsie\tPPER\tnsf3\tHD\tfoo\n#EOS 1\n", 2),
+            ("#BOS 1\nSie\tsie\tPPER\tnsf3\tHD\t500\n", 2),
+        ] {
+            match Reader::new(Cursor::new(data)).next() {
+                Some(Err(ExportError::Parse { line: err_line, .. })) => assert_eq!(err_line, *line),
+                _ => panic!("Invalid sentence was accepted: {}", data),
+            }
+        }
+
+        match Reader::new(Cursor::new("#FORMAT 3\n")).next() {
+            Some(Err(ExportError::UnsupportedVersion { line: 1, .. })) => (),
+            _ => panic!("Unsupported version was accepted"),
+        }
+    }
+}
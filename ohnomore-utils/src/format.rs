@@ -0,0 +1,196 @@
+//! Corpus formats of the command-line utilities.
+
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use conllu::io::WriteSentence;
+use ohnomore::transform::DependencyGraph;
+use thiserror::Error;
+
+use crate::export::{self, ExportError};
+use crate::jsonl::{self, JsonlError};
+use crate::vrt::{self, VrtColumns, VrtError};
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error(transparent)]
+    Conllu(#[from] conllu::Error),
+
+    #[error(transparent)]
+    Export(#[from] ExportError),
+
+    #[error(transparent)]
+    Jsonl(#[from] JsonlError),
+
+    #[error(transparent)]
+    Vrt(#[from] VrtError),
+
+    #[error("Columns can only be specified for the VRT format")]
+    ColumnsWithoutVrt,
+
+    #[error("Unknown corpus format: {0}")]
+    UnknownFormat(String),
+}
+
+/// Corpus format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// CoNLL-U.
+    Conllu,
+
+    /// NEGRA/TüBa-D/Z export format, see the `export` module.
+    Export,
+
+    /// JSON Lines, see the `jsonl` module.
+    Jsonl,
+
+    /// CWB vertical format, see the `vrt` module.
+    Vrt(VrtColumns),
+}
+
+impl Format {
+    /// Check whether MISC features can be stored in this format.
+    pub fn has_misc(&self) -> bool {
+        match self {
+            Format::Vrt(columns) => columns.has_misc(),
+            _ => true,
+        }
+    }
+
+    /// Replace the columns of the VRT format.
+    ///
+    /// Returns an error for other formats.
+    pub fn with_vrt_columns(self, columns: VrtColumns) -> Result<Self, FormatError> {
+        match self {
+            Format::Vrt(_) => Ok(Format::Vrt(columns)),
+            _ => Err(FormatError::ColumnsWithoutVrt),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "conllu" => Ok(Format::Conllu),
+            "export" => Ok(Format::Export),
+            "jsonl" => Ok(Format::Jsonl),
+            "vrt" => Ok(Format::Vrt(VrtColumns::default())),
+            _ => Err(FormatError::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+/// Process a corpus sentence by sentence.
+///
+/// Sentences are read from `read` in the given format, passed to
+/// `process_sentence`, and then written to `write` in the same format.
+pub fn process<R, W, F>(
+    format: Format,
+    read: R,
+    write: W,
+    mut process_sentence: F,
+) -> Result<(), FormatError>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(&mut dyn DependencyGraph),
+{
+    match format {
+        Format::Conllu => {
+            let mut writer = conllu::io::Writer::new(write);
+            for sentence in conllu::io::Reader::new(read) {
+                let mut sentence = sentence?;
+                process_sentence(&mut sentence);
+                writer.write_sentence(&sentence)?;
+            }
+        }
+        Format::Export => {
+            let mut reader = export::Reader::new(read);
+            let mut writer = export::Writer::new(write);
+            for sentence in &mut reader {
+                let mut sentence = sentence?;
+                process_sentence(&mut sentence);
+                writer.write_sentence(&sentence)?;
+            }
+            writer.write_lines(reader.trailer())?;
+        }
+        Format::Jsonl => {
+            let mut writer = jsonl::Writer::new(write);
+            for sentence in jsonl::Reader::new(read) {
+                let mut sentence = sentence?;
+                process_sentence(&mut sentence);
+                writer.write_sentence(&sentence)?;
+            }
+        }
+        Format::Vrt(columns) => {
+            let mut reader = vrt::Reader::new(read, columns);
+            let mut writer = vrt::Writer::new(write);
+            for sentence in &mut reader {
+                let mut sentence = sentence?;
+                process_sentence(&mut sentence);
+                writer.write_sentence(&sentence)?;
+            }
+            writer.write_lines(reader.trailer())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ohnomore::transform::pipeline;
+    use ohnomore::transform::Transforms;
+
+    use crate::vrt::VrtColumns;
+
+    use super::{process, Format, FormatError};
+
+    #[test]
+    pub fn parses_formats() {
+        assert_eq!("conllu".parse::<Format>().unwrap(), Format::Conllu);
+        assert_eq!("export".parse::<Format>().unwrap(), Format::Export);
+        assert_eq!("jsonl".parse::<Format>().unwrap(), Format::Jsonl);
+        assert_eq!(
+            "vrt".parse::<Format>().unwrap(),
+            Format::Vrt(VrtColumns::default())
+        );
+        assert!(matches!(
+            "xml".parse::<Format>(),
+            Err(FormatError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    pub fn processes_conllu_and_jsonl() {
+        let transforms = Transforms::new(pipeline::lemmatization());
+
+        let conllu = "1\tEr\ter\tPRON\tPPER\t_\t2\tSUBJ\t_\t_\n\
+                      2\tfängt\tfangen\tVERB\tVVFIN\t_\t0\tROOT\t_\t_\n\
+                      3\tan\tan\tADP\tPTKVZ\t_\t2\tAVZ\t_\t_\n";
+        let mut output = Vec::new();
+        process(Format::Conllu, Cursor::new(conllu), &mut output, |graph| {
+            transforms.transform(graph)
+        })
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            conllu.replace("\tfangen\t", "\tan#fangen\t")
+        );
+
+        let jsonl = r#"{"tokens":[{"form":"Er","lemma":"er","xpos":"PPER","head":2,"deprel":"SUBJ"},{"form":"fängt","lemma":"fangen","xpos":"VVFIN","head":0,"deprel":"ROOT"},{"form":"an","lemma":"an","xpos":"PTKVZ","head":2,"deprel":"AVZ"}]}"#;
+        let mut output = Vec::new();
+        process(Format::Jsonl, Cursor::new(jsonl), &mut output, |graph| {
+            transforms.transform(graph)
+        })
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            jsonl.replace(r#""fangen""#, r#""an#fangen""#) + "\n"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! JSON Lines sentences.
+//!
+//! Every line is a JSON object that represents a sentence. The tokens of
+//! the sentence are stored in the `tokens` array:
+//!
+//! ```json
+//! {"tokens": [{"form": "Er", "lemma": "er", "upos": "PRON", "xpos": "PPER", "head": 2, "deprel": "SUBJ"},
+//!             {"form": "lacht", "lemma": "lachen", "upos": "VERB", "xpos": "VVFIN", "head": 0, "deprel": "ROOT"}]}
+//! ```
+//!
+//! Heads are numbered as in CoNLL-U, where *0* is the root and tokens are
+//! numbered from *1*. All token fields, except for `form`, are optional.
+//! The transformations can add features to the `misc` object of a token.
+//! Other fields of sentences and tokens are preserved.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use ohnomore::transform::{DependencyGraph, Token, TokenMut};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JsonlError {
+    #[error(transparent)]
+    IO(#[from] io::Error),
+
+    #[error("Invalid sentence on line {line}: {source}")]
+    Json {
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid head on line {line}: {head}")]
+    InvalidHead { line: usize, head: usize },
+}
+
+/// A sentence in JSON Lines format.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonSentence {
+    tokens: Vec<JsonToken>,
+
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl JsonSentence {
+    /// Get the tokens of the sentence.
+    pub fn tokens(&self) -> &[JsonToken] {
+        &self.tokens
+    }
+}
+
+impl DependencyGraph for JsonSentence {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(
+            self.tokens
+                .iter()
+                .enumerate()
+                .filter(move |(_, token)| token.head == Some(idx))
+                .map(|(dependent, token)| (dependent + 1, token.deprel.as_deref().unwrap_or("_"))),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        let token = &self.tokens[idx.checked_sub(1)?];
+        token
+            .head
+            .map(|head| (head, token.deprel.as_deref().unwrap_or("_")))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        assert!(idx != 0, "The root node was used as a token");
+        &self.tokens[idx - 1]
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        assert!(idx != 0, "The root node was used as a token");
+        &mut self.tokens[idx - 1]
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len() + 1
+    }
+}
+
+/// A token in JSON Lines format.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonToken {
+    form: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lemma: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    upos: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    xpos: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    head: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprel: Option<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    misc: BTreeMap<String, String>,
+
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl Token for JsonToken {
+    fn form(&self) -> &str {
+        &self.form
+    }
+
+    fn lemma(&self) -> &str {
+        self.lemma.as_deref().unwrap_or("_")
+    }
+
+    fn upos(&self) -> &str {
+        self.upos.as_deref().unwrap_or("_")
+    }
+
+    fn xpos(&self) -> &str {
+        self.xpos.as_deref().unwrap_or("_")
+    }
+
+    fn optional_lemma(&self) -> Option<&str> {
+        self.lemma.as_deref()
+    }
+}
+
+impl TokenMut for JsonToken {
+    fn set_lemma(&mut self, lemma: Option<String>) {
+        self.lemma = lemma;
+    }
+
+    fn set_misc(&mut self, key: &str, value: String) {
+        self.misc.insert(key.to_owned(), value);
+    }
+}
+
+/// Reader for sentences in JSON Lines format.
+pub struct Reader<R> {
+    read: R,
+    line: usize,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(read: R) -> Self {
+        Reader { read, line: 0 }
+    }
+
+    /// Read a sentence, returning `None` at the end of the input.
+    pub fn read_sentence(&mut self) -> Result<Option<JsonSentence>, JsonlError> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.read.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            // Skip empty lines.
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let sentence: JsonSentence =
+            serde_json::from_str(&line).map_err(|source| JsonlError::Json {
+                line: self.line,
+                source,
+            })?;
+
+        for token in &sentence.tokens {
+            if let Some(head) = token.head {
+                if head > sentence.tokens.len() {
+                    return Err(JsonlError::InvalidHead {
+                        line: self.line,
+                        head,
+                    });
+                }
+            }
+        }
+
+        Ok(Some(sentence))
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<JsonSentence, JsonlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_sentence().transpose()
+    }
+}
+
+/// Writer for sentences in JSON Lines format.
+pub struct Writer<W> {
+    write: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(write: W) -> Self {
+        Writer { write }
+    }
+
+    /// Write a sentence as a line.
+    pub fn write_sentence(&mut self, sentence: &JsonSentence) -> Result<(), JsonlError> {
+        serde_json::to_writer(&mut self.write, sentence).map_err(io::Error::from)?;
+        writeln!(self.write)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ohnomore::transform::{pipeline, DependencyGraph, Transforms};
+
+    use super::{JsonlError, Reader, Writer};
+
+    static SENTENCE: &str = r#"{"tokens":[{"form":"Er","lemma":"er","xpos":"PPER","head":2,"deprel":"SUBJ"},{"form":"fängt","lemma":"fangen","xpos":"VVFIN","head":0,"deprel":"ROOT","score":0.5},{"form":"an","lemma":"an","xpos":"PTKVZ","head":2,"deprel":"AVZ"}],"id":"s1"}"#;
+
+    #[test]
+    pub fn reads_sentences() {
+        let data = format!("{}\n\n{}\n", SENTENCE, SENTENCE);
+        let sentences = Reader::new(Cursor::new(data))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(sentences.len(), 2);
+
+        let sentence = &sentences[0];
+        assert_eq!(sentence.len(), 4);
+        assert_eq!(sentence.token(2).xpos(), "VVFIN");
+        assert_eq!(sentence.token(2).upos(), "_");
+        assert_eq!(
+            sentence.dependents(2).collect::<Vec<_>>(),
+            vec![(1, "SUBJ"), (3, "AVZ")]
+        );
+        assert_eq!(sentence.head(2), Some((0, "ROOT")));
+    }
+
+    #[test]
+    pub fn roundtrips_sentences() {
+        let mut sentence = Reader::new(Cursor::new(SENTENCE)).next().unwrap().unwrap();
+
+        Transforms::new(pipeline::lemmatization()).transform_to_misc(&mut sentence, "TuebaLemma");
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_sentence(&sentence).unwrap();
+        let output = String::from_utf8(writer.write).unwrap();
+
+        assert_eq!(
+            output,
+            SENTENCE
+                .replace(
+                    r#""head":0,"deprel":"ROOT","score":0.5"#,
+                    r#""head":0,"deprel":"ROOT","misc":{"TuebaLemma":"an#fangen"},"score":0.5"#
+                )
+                .replace(
+                    r#""head":2,"deprel":"SUBJ""#,
+                    r#""head":2,"deprel":"SUBJ","misc":{"TuebaLemma":"er"}"#
+                )
+                .replace(
+                    r#""head":2,"deprel":"AVZ""#,
+                    r#""head":2,"deprel":"AVZ","misc":{"TuebaLemma":"an"}"#
+                )
+                + "\n"
+        );
+    }
+
+    #[test]
+    pub fn rejects_invalid_sentences() {
+        let mut reader = Reader::new(Cursor::new("\n{\"tokens\": [{\"lemma\": \"er\"}]}\n"));
+        match reader.next() {
+            Some(Err(JsonlError::Json { line: 2, .. })) => (),
+            _ => panic!("Invalid sentence was accepted"),
+        }
+
+        let mut reader = Reader::new(Cursor::new(
+            "{\"tokens\": [{\"form\": \"er\", \"head\": 2}]}\n",
+        ));
+        match reader.next() {
+            Some(Err(JsonlError::InvalidHead { line: 1, head: 2 })) => (),
+            _ => panic!("Invalid head was accepted"),
+        }
+    }
+}
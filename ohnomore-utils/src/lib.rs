@@ -0,0 +1,9 @@
+pub mod export;
+
+pub mod format;
+
+pub mod jsonl;
+
+mod stts;
+
+pub mod vrt;
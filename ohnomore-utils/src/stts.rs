@@ -0,0 +1,27 @@
+//! STTS part-of-speech tags.
+
+/// Map an STTS tag to a universal part-of-speech tag.
+///
+/// Truncations are mapped to *NOUN* when the lemma is capitalized and to
+/// *X* otherwise.
+pub(crate) fn universal_tag(tag: &str, lemma: &str) -> &'static str {
+    match tag {
+        "ADJA" | "ADJD" => "ADJ",
+        "ADV" | "PAV" | "PROAV" | "PWAV" => "ADV",
+        "APPR" | "APPRART" | "APPO" | "APZR" | "PTKVZ" => "ADP",
+        "ART" | "PDAT" | "PIAT" | "PIDAT" | "PPOSAT" | "PRELAT" | "PWAT" => "DET",
+        "CARD" => "NUM",
+        "ITJ" => "INTJ",
+        "KON" | "KOKOM" => "CCONJ",
+        "KOUI" | "KOUS" => "SCONJ",
+        "NN" => "NOUN",
+        "NE" => "PROPN",
+        "PDS" | "PIS" | "PPER" | "PPOSS" | "PRELS" | "PRF" | "PWS" => "PRON",
+        "PTKA" | "PTKANT" | "PTKNEG" | "PTKZU" => "PART",
+        "VVFIN" | "VVIMP" | "VVINF" | "VVIZU" | "VVPP" => "VERB",
+        "VAFIN" | "VAIMP" | "VAINF" | "VAPP" | "VMFIN" | "VMINF" | "VMPP" => "AUX",
+        "$," | "$." | "$(" => "PUNCT",
+        "TRUNC" if lemma.chars().next().map(char::is_uppercase) == Some(true) => "NOUN",
+        _ => "X",
+    }
+}
@@ -0,0 +1,578 @@
+//! CWB vertical (VRT) sentences.
+//!
+//! A vertical file has one token per line, with tab-separated positional
+//! attributes. Structural attributes are XML-like tags on separate lines.
+//! Each sentence is enclosed in `<s>` and `</s>` tags. Other tags, within
+//! and outside sentences, are preserved.
+//!
+//! The meaning of the positional attributes is configured with
+//! `VrtColumns`. The form, lemma and (STTS) part-of-speech columns are
+//! required. Universal part-of-speech tags are derived from the STTS tags
+//! when there is no `upos` column. Dependency relations are read from the
+//! `head` and `deprel` columns, where heads are numbered as in CoNLL-U.
+//! MISC features can only be stored when there is a `misc` column.
+
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use ohnomore::transform::{DependencyGraph, Token, TokenMut};
+use thiserror::Error;
+
+use crate::stts::universal_tag;
+
+static EMPTY_FIELD: &str = "_";
+
+#[derive(Debug, Error)]
+pub enum VrtError {
+    #[error(transparent)]
+    IO(#[from] io::Error),
+
+    #[error("Invalid VRT columns: {0}")]
+    InvalidColumns(String),
+
+    #[error("Invalid VRT data on line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+}
+
+/// Positional attributes of a vertical file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VrtColumns {
+    len: usize,
+    form: usize,
+    lemma: usize,
+    pos: usize,
+    upos: Option<usize>,
+    head: Option<usize>,
+    deprel: Option<usize>,
+    misc: Option<usize>,
+}
+
+impl VrtColumns {
+    /// Check whether MISC features can be stored.
+    pub fn has_misc(&self) -> bool {
+        self.misc.is_some()
+    }
+}
+
+impl Default for VrtColumns {
+    /// The columns `form,pos,lemma`.
+    fn default() -> Self {
+        VrtColumns {
+            len: 3,
+            form: 0,
+            lemma: 2,
+            pos: 1,
+            upos: None,
+            head: None,
+            deprel: None,
+            misc: None,
+        }
+    }
+}
+
+impl FromStr for VrtColumns {
+    type Err = VrtError;
+
+    /// Parse a comma-separated list of column names.
+    ///
+    /// The names are `form`, `lemma`, `pos`, `upos`, `head`, `deprel`,
+    /// and `misc`. Other columns are named `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut form = None;
+        let mut lemma = None;
+        let mut pos = None;
+        let mut upos = None;
+        let mut head = None;
+        let mut deprel = None;
+        let mut misc = None;
+
+        let names = s.split(',').map(str::trim).collect::<Vec<_>>();
+        for (idx, &name) in names.iter().enumerate() {
+            let column = match name {
+                "form" => &mut form,
+                "lemma" => &mut lemma,
+                "pos" => &mut pos,
+                "upos" => &mut upos,
+                "head" => &mut head,
+                "deprel" => &mut deprel,
+                "misc" => &mut misc,
+                "_" => continue,
+                _ => return Err(VrtError::InvalidColumns(format!("unknown column {}", name))),
+            };
+
+            if column.replace(idx).is_some() {
+                return Err(VrtError::InvalidColumns(format!(
+                    "duplicate column {}",
+                    name
+                )));
+            }
+        }
+
+        let required = |column: Option<usize>, name| {
+            column.ok_or_else(|| VrtError::InvalidColumns(format!("{} column missing", name)))
+        };
+
+        if head.is_some() != deprel.is_some() {
+            return Err(VrtError::InvalidColumns(
+                "head and deprel columns must be used together".to_owned(),
+            ));
+        }
+
+        Ok(VrtColumns {
+            len: names.len(),
+            form: required(form, "form")?,
+            lemma: required(lemma, "lemma")?,
+            pos: required(pos, "pos")?,
+            upos,
+            head,
+            deprel,
+            misc,
+        })
+    }
+}
+
+enum SentenceLine {
+    Tag(String),
+    Token,
+}
+
+/// A sentence in VRT format.
+pub struct VrtSentence {
+    preamble: Vec<String>,
+    lines: Vec<SentenceLine>,
+    tokens: Vec<VrtToken>,
+}
+
+impl VrtSentence {
+    /// Get the tokens of the sentence.
+    pub fn tokens(&self) -> &[VrtToken] {
+        &self.tokens
+    }
+}
+
+impl DependencyGraph for VrtSentence {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(
+            self.tokens
+                .iter()
+                .enumerate()
+                .filter(move |(_, token)| token.head == Some(idx))
+                .map(|(dependent, token)| (dependent + 1, token.deprel())),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        let token = &self.tokens[idx.checked_sub(1)?];
+        token.head.map(|head| (head, token.deprel()))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        assert!(idx != 0, "The root node was used as a token");
+        &self.tokens[idx - 1]
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        assert!(idx != 0, "The root node was used as a token");
+        &mut self.tokens[idx - 1]
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len() + 1
+    }
+}
+
+/// A token in VRT format.
+pub struct VrtToken {
+    columns: VrtColumns,
+    fields: Vec<String>,
+    upos: &'static str,
+    head: Option<usize>,
+}
+
+impl VrtToken {
+    /// Get the dependency relation of the token.
+    pub fn deprel(&self) -> &str {
+        self.columns
+            .deprel
+            .map(|deprel| self.fields[deprel].as_str())
+            .unwrap_or(EMPTY_FIELD)
+    }
+
+    /// Get the head of the token.
+    pub fn head(&self) -> Option<usize> {
+        self.head
+    }
+}
+
+impl Token for VrtToken {
+    fn form(&self) -> &str {
+        &self.fields[self.columns.form]
+    }
+
+    fn lemma(&self) -> &str {
+        &self.fields[self.columns.lemma]
+    }
+
+    fn upos(&self) -> &str {
+        match self.columns.upos {
+            Some(upos) => &self.fields[upos],
+            None => self.upos,
+        }
+    }
+
+    fn xpos(&self) -> &str {
+        &self.fields[self.columns.pos]
+    }
+}
+
+impl TokenMut for VrtToken {
+    fn set_lemma(&mut self, lemma: Option<String>) {
+        self.fields[self.columns.lemma] = lemma.unwrap_or_else(|| EMPTY_FIELD.to_owned());
+    }
+
+    /// Set a MISC feature.
+    ///
+    /// The feature is discarded when there is no `misc` column. Use
+    /// `VrtColumns::has_misc` to check whether features can be stored.
+    fn set_misc(&mut self, key: &str, value: String) {
+        let misc = match self.columns.misc {
+            Some(misc) => &mut self.fields[misc],
+            None => return,
+        };
+
+        let mut features = misc
+            .split('|')
+            .filter(|feature| *feature != EMPTY_FIELD && feature.split('=').next() != Some(key))
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        features.push(format!("{}={}", key, value));
+
+        *misc = features.join("|");
+    }
+}
+
+fn is_tag(line: &str) -> bool {
+    line.starts_with('<') && line.ends_with('>')
+}
+
+fn is_sentence_start(line: &str) -> bool {
+    line == "<s>" || line.starts_with("<s ")
+}
+
+/// Reader for sentences in VRT format.
+pub struct Reader<R> {
+    read: R,
+    columns: VrtColumns,
+    line: usize,
+    trailer: Vec<String>,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(read: R, columns: VrtColumns) -> Self {
+        Reader {
+            read,
+            columns,
+            line: 0,
+            trailer: Vec::new(),
+        }
+    }
+
+    /// Get the lines after the last sentence.
+    ///
+    /// The lines are only available after all sentences were read.
+    pub fn trailer(&self) -> &[String] {
+        &self.trailer
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, VrtError> {
+        let mut line = String::new();
+        if self.read.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+
+        let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+        line.truncate(len);
+
+        Ok(Some(line))
+    }
+
+    fn parse_token(&self, line: &str) -> Result<VrtToken, VrtError> {
+        let fields = line.split('\t').map(ToOwned::to_owned).collect::<Vec<_>>();
+        if fields.len() < self.columns.len {
+            return Err(VrtError::Parse {
+                line: self.line,
+                reason: format!(
+                    "expected {} columns, found {}",
+                    self.columns.len,
+                    fields.len()
+                ),
+            });
+        }
+
+        let head = match self.columns.head.map(|head| fields[head].as_str()) {
+            Some(head) if head != EMPTY_FIELD => {
+                Some(head.parse().map_err(|_| VrtError::Parse {
+                    line: self.line,
+                    reason: format!("invalid head: {}", head),
+                })?)
+            }
+            _ => None,
+        };
+
+        Ok(VrtToken {
+            upos: universal_tag(&fields[self.columns.pos], &fields[self.columns.lemma]),
+            columns: self.columns,
+            fields,
+            head,
+        })
+    }
+
+    /// Read a sentence, returning `None` at the end of the input.
+    pub fn read_sentence(&mut self) -> Result<Option<VrtSentence>, VrtError> {
+        let mut preamble = Vec::new();
+
+        loop {
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => {
+                    self.trailer = preamble;
+                    return Ok(None);
+                }
+            };
+
+            if is_sentence_start(&line) {
+                preamble.push(line);
+                break;
+            }
+
+            if !is_tag(&line) {
+                return Err(VrtError::Parse {
+                    line: self.line,
+                    reason: "token outside sentence".to_owned(),
+                });
+            }
+
+            preamble.push(line);
+        }
+
+        let mut lines = Vec::new();
+        let mut tokens = Vec::new();
+
+        loop {
+            let line = self.read_line()?.ok_or_else(|| VrtError::Parse {
+                line: self.line,
+                reason: "sentence without </s>".to_owned(),
+            })?;
+
+            if is_tag(&line) {
+                let end = line == "</s>";
+                lines.push(SentenceLine::Tag(line));
+                if end {
+                    break;
+                }
+            } else {
+                tokens.push(self.parse_token(&line)?);
+                lines.push(SentenceLine::Token);
+            }
+        }
+
+        for token in &tokens {
+            if let Some(head) = token.head {
+                if head > tokens.len() {
+                    return Err(VrtError::Parse {
+                        line: self.line,
+                        reason: format!("invalid head: {}", head),
+                    });
+                }
+            }
+        }
+
+        Ok(Some(VrtSentence {
+            preamble,
+            lines,
+            tokens,
+        }))
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<VrtSentence, VrtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_sentence().transpose()
+    }
+}
+
+/// Writer for sentences in VRT format.
+pub struct Writer<W> {
+    write: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(write: W) -> Self {
+        Writer { write }
+    }
+
+    /// Write a sentence, including the tags that preceded it.
+    pub fn write_sentence(&mut self, sentence: &VrtSentence) -> Result<(), VrtError> {
+        self.write_lines(&sentence.preamble)?;
+
+        let mut tokens = sentence.tokens.iter();
+        for line in &sentence.lines {
+            match line {
+                SentenceLine::Tag(tag) => writeln!(self.write, "{}", tag)?,
+                SentenceLine::Token => {
+                    let token = tokens.next().expect("Sentence without token");
+                    writeln!(self.write, "{}", token.fields.join("\t"))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write lines verbatim, e.g. the trailer of a reader.
+    pub fn write_lines(&mut self, lines: &[String]) -> Result<(), VrtError> {
+        for line in lines {
+            writeln!(self.write, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ohnomore::transform::{pipeline, DependencyGraph, Transforms};
+
+    use super::{Reader, VrtColumns, VrtError, Writer};
+
+    static CORPUS: &str = "<text id=\"t1\">\n\
+<s>\n\
+Er\tPPER\ter\t2\tSUBJ\t_\n\
+fängt\tVVFIN\tfangen\t0\tROOT\t_\n\
+<ne type=\"TMP\">\n\
+heute\tADV\theute\t2\tADV\t_\n\
+</ne>\n\
+an\tPTKVZ\tan\t2\tAVZ\tSpaceAfter=No\n\
+.\t$.\t.\t2\t-PUNCT-\t_\n\
+</s>\n\
+</text>\n";
+
+    fn columns() -> VrtColumns {
+        "form,pos,lemma,head,deprel,misc".parse().unwrap()
+    }
+
+    #[test]
+    pub fn parses_columns() {
+        assert_eq!(
+            "form,pos,lemma".parse::<VrtColumns>().unwrap(),
+            VrtColumns::default()
+        );
+        assert!(columns().has_misc());
+
+        for columns in &[
+            "form,pos",
+            "form,pos,lemma,lemma",
+            "form,pos,lemma,head",
+            "word",
+        ] {
+            assert!(matches!(
+                columns.parse::<VrtColumns>(),
+                Err(VrtError::InvalidColumns(_))
+            ));
+        }
+    }
+
+    #[test]
+    pub fn reads_sentences() {
+        let mut reader = Reader::new(Cursor::new(CORPUS), columns());
+        let sentence = reader.next().unwrap().unwrap();
+        assert!(reader.next().is_none());
+        assert_eq!(reader.trailer(), &["</text>".to_owned()]);
+
+        assert_eq!(sentence.len(), 6);
+        assert_eq!(sentence.token(3).form(), "heute");
+        assert_eq!(sentence.token(3).xpos(), "ADV");
+        assert_eq!(sentence.token(3).upos(), "ADV");
+        assert_eq!(
+            sentence.dependents(2).collect::<Vec<_>>(),
+            vec![(1, "SUBJ"), (3, "ADV"), (4, "AVZ"), (5, "-PUNCT-")]
+        );
+        assert_eq!(sentence.head(4), Some((2, "AVZ")));
+    }
+
+    #[test]
+    pub fn roundtrips_sentences() {
+        let mut reader = Reader::new(Cursor::new(CORPUS), columns());
+        let mut writer = Writer::new(Vec::new());
+        let transforms = Transforms::new(pipeline::lemmatization());
+
+        for sentence in &mut reader {
+            let mut sentence = sentence.unwrap();
+            transforms.transform_to_misc(&mut sentence, "TuebaLemma");
+            writer.write_sentence(&sentence).unwrap();
+        }
+        writer.write_lines(reader.trailer()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.write).unwrap(),
+            CORPUS
+                .replace("ROOT\t_", "ROOT\tTuebaLemma=an#fangen")
+                .replace("SUBJ\t_", "SUBJ\tTuebaLemma=er")
+                .replace("ADV\t_", "ADV\tTuebaLemma=heute")
+                .replace("SpaceAfter=No", "SpaceAfter=No|TuebaLemma=an")
+                .replace("-PUNCT-\t_", "-PUNCT-\tTuebaLemma=.")
+        );
+    }
+
+    #[test]
+    pub fn discards_misc_without_misc_column() {
+        let corpus = "<s>\nEr\tPPER\ter\n</s>\n";
+        let mut reader = Reader::new(Cursor::new(corpus), VrtColumns::default());
+        let mut writer = Writer::new(Vec::new());
+
+        let mut sentence = reader.next().unwrap().unwrap();
+        sentence
+            .token_mut(1)
+            .set_misc("TuebaLemma", "er".to_owned());
+        writer.write_sentence(&sentence).unwrap();
+
+        assert_eq!(String::from_utf8(writer.write).unwrap(), corpus);
+    }
+
+    #[test]
+    pub fn rejects_invalid_sentences() {
+        for (data, line) in &[
+            ("Er\tPPER\ter\n", 1),
+            ("<s>\nEr\tPPER\n</s>\n", 2),
+            ("<s>\nEr\tPPER\ter\n", 2),
+        ] {
+            match Reader::new(Cursor::new(data), VrtColumns::default()).next() {
+                Some(Err(VrtError::Parse { line: err_line, .. })) => assert_eq!(err_line, *line),
+                _ => panic!("Invalid sentence was accepted: {}", data),
+            }
+        }
+
+        match Reader::new(
+            Cursor::new("<s>\nEr\tPPER\ter\t2\tSUBJ\t_\n</s>\n"),
+            columns(),
+        )
+        .next()
+        {
+            Some(Err(VrtError::Parse { line: 3, .. })) => (),
+            _ => panic!("Invalid head was accepted"),
+        }
+    }
+}
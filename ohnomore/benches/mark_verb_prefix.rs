@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fst::Set;
+use ohnomore::automaton::{PrefixStar, Prefixes};
+use ohnomore::data::DataBundle;
+use ohnomore::transform::lemmatization::MarkVerbPrefix;
+use ohnomore::transform::Transform;
+use udgraph::graph::Sentence;
+use udgraph::token::TokenBuilder;
+
+/// Get the prefixes and base verb of every separable verb in the
+/// TüBa-D/Z 10 verb list.
+fn tdz10_verbs() -> Vec<(String, &'static str)> {
+    include_str!("../data/tdz10-separable-verbs.txt")
+        .lines()
+        // Skip alternatives and entries with special markup.
+        .filter(|line| line.chars().all(|c| c.is_alphabetic() || c == '#'))
+        .map(|line| {
+            let mut parts: Vec<&str> = line.split('#').collect();
+            let verb = parts.pop().unwrap();
+            (parts.concat(), verb)
+        })
+        .collect()
+}
+
+/// Construct a sentence with the infinitive and the zu-infinitive of
+/// every separable verb in the TüBa-D/Z 10 verb list. The lemma of each
+/// token is the verb without its separable prefixes.
+fn tdz10_verb_forms() -> Sentence {
+    let mut sentence = Sentence::new();
+
+    for (prefixes, verb) in tdz10_verbs() {
+        sentence.push(
+            TokenBuilder::new(format!("{}{}", prefixes, verb))
+                .lemma(verb)
+                .upos("VERB")
+                .xpos("VVINF")
+                .into(),
+        );
+        sentence.push(
+            TokenBuilder::new(format!("{}zu{}", prefixes, verb))
+                .lemma(verb)
+                .upos("VERB")
+                .xpos("VVIZU")
+                .into(),
+        );
+    }
+
+    sentence
+}
+
+/// Candidate list of prefixes and the corresponding stripped form.
+struct PrefixesCandidate<'a> {
+    stripped_form: &'a str,
+    prefixes: Vec<String>,
+}
+
+/// The breadth-first search for matches of (prefix)* that preceded
+/// `PrefixStar`, retained as a baseline.
+fn prefix_star_bfs<'a, D>(prefix_set: &Set<D>, s: &'a str) -> Vec<PrefixesCandidate<'a>>
+where
+    D: AsRef<[u8]>,
+{
+    let mut result = Vec::new();
+
+    let mut q = VecDeque::new();
+    q.push_back(PrefixesCandidate {
+        stripped_form: s,
+        prefixes: Vec::new(),
+    });
+
+    while let Some(PrefixesCandidate {
+        stripped_form,
+        prefixes,
+    }) = q.pop_front()
+    {
+        result.push(PrefixesCandidate {
+            stripped_form,
+            prefixes: prefixes.clone(),
+        });
+
+        for prefix in prefix_set.prefixes(stripped_form) {
+            let mut prefixes = prefixes.clone();
+            let prefix_len = prefix.len();
+            prefixes.push(prefix.to_owned());
+            q.push_back(PrefixesCandidate {
+                stripped_form: &stripped_form[prefix_len..],
+                prefixes,
+            });
+        }
+    }
+
+    result
+}
+
+fn mark_verb_prefix(c: &mut Criterion) {
+    let sentence = tdz10_verb_forms();
+    let transform = MarkVerbPrefix::new();
+
+    c.bench_function("mark_verb_prefix_tdz10", |b| {
+        b.iter(|| {
+            for idx in 1..sentence.len() {
+                black_box(transform.transform(&sentence, idx));
+            }
+        })
+    });
+}
+
+fn prefix_search(c: &mut Criterion) {
+    let forms: Vec<_> = tdz10_verbs()
+        .into_iter()
+        .flat_map(|(prefixes, verb)| {
+            vec![
+                format!("{}{}", prefixes, verb),
+                format!("{}zu{}", prefixes, verb),
+            ]
+        })
+        .collect();
+    let prefix_set = DataBundle::default().separable_prefixes();
+
+    let mut group = c.benchmark_group("prefix_search_tdz10");
+    group.bench_function("breadth_first", |b| {
+        b.iter(|| {
+            for form in &forms {
+                black_box(prefix_star_bfs(&prefix_set, form));
+            }
+        })
+    });
+    group.bench_function("prefix_star", |b| {
+        b.iter(|| {
+            for form in &forms {
+                prefix_set.prefix_star(form, |offsets| {
+                    black_box(offsets);
+                });
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, mark_verb_prefix, prefix_search);
+criterion_main!(benches);
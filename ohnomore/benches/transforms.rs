@@ -0,0 +1,342 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use criterion::measurement::WallTime;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion};
+use ohnomore::transform::delemmatization::{
+    RemoveAlternatives, RemoveReflexiveTag, RemoveSepVerbPrefix, RemoveSeparatedVerbPrefix,
+    RemoveTruncMarker,
+};
+use ohnomore::transform::lemmatization::{
+    AddContextualReflexiveTag, AddReflexiveTag, AddSeparableVerbPrefixes, AddSeparatedVerbPrefix,
+    AddSeparatedVerbPrefixLinear, FormAsLemma, MarkVerbPrefix, ReflexiveConfidence, RestoreCase,
+};
+use ohnomore::transform::misc::{
+    SimplifyArticleLemma, SimplifyPIAT, SimplifyPIDAT, SimplifyPIS, SimplifyPersonalPronounLemma,
+    SimplifyPossesivePronounLemma,
+};
+use ohnomore::transform::test_cases::{read_test_cases, TestGraph};
+use ohnomore::transform::{pipeline, DependencyGraph, Transform, Transforms};
+use udgraph::graph::{DepTriple, Sentence};
+use udgraph::token::TokenBuilder;
+
+/// The number of sentences in the synthetic corpus.
+const SYNTHETIC_CORPUS_LEN: usize = 1000;
+
+/// Named entities that are used in the synthetic corpus.
+static NAMED_ENTITIES: [(&str, &str); 6] = [
+    ("Müller", "müller"),
+    ("McDonald's", "mcdonald's"),
+    ("Baden-Württemberg", "baden-württemberg"),
+    ("FDP", "fdp"),
+    ("Schröders", "schröder"),
+    ("Ostberliner", "ostberliner"),
+];
+
+/// Construct a sentence from (form, lemma, xpos) triples and
+/// (head, relation, dependent) edges.
+fn sentence(tokens: &[(&str, &str, &str)], edges: &[(usize, &str, usize)]) -> Sentence {
+    let mut sentence = Sentence::new();
+
+    for &(form, lemma, xpos) in tokens {
+        sentence.push(
+            TokenBuilder::new(form)
+                .lemma(lemma)
+                .upos("_")
+                .xpos(xpos)
+                .into(),
+        );
+    }
+
+    for &(head, rel, dependent) in edges {
+        sentence
+            .dep_graph_mut()
+            .add_deprel(DepTriple::new(head, Some(rel), dependent))
+            .expect("Invalid dependency relation");
+    }
+
+    sentence
+}
+
+/// Construct a corpus of sentences with predicted lemmas.
+///
+/// The sentences contain the phenomena that the transformations deal
+/// with, such as named entities, separated verb prefixes, reflexives,
+/// and indefinite pronouns.
+fn synthetic_corpus() -> Vec<Sentence> {
+    (0..SYNTHETIC_CORPUS_LEN)
+        .map(|idx| {
+            let (ne_form, ne_lemma) = NAMED_ENTITIES[idx % NAMED_ENTITIES.len()];
+            match idx % 3 {
+                0 => sentence(
+                    &[
+                        ("Peter", "peter", "NE"),
+                        (ne_form, ne_lemma, "NE"),
+                        ("fängt", "fangen", "VVFIN"),
+                        ("heute", "heute", "ADV"),
+                        ("mit", "mit", "APPR"),
+                        ("der", "der", "ART"),
+                        ("Arbeit", "arbeit", "NN"),
+                        ("wieder", "wieder", "ADV"),
+                        ("an", "an", "PTKVZ"),
+                        (".", ".", "$."),
+                    ],
+                    &[
+                        (0, "ROOT", 3),
+                        (3, "SUBJ", 1),
+                        (1, "APP", 2),
+                        (3, "ADV", 4),
+                        (3, "PP", 5),
+                        (5, "PN", 7),
+                        (7, "DET", 6),
+                        (3, "ADV", 8),
+                        (3, "AVZ", 9),
+                        (3, "-PUNCT-", 10),
+                    ],
+                ),
+                1 => sentence(
+                    &[
+                        ("Sie", "sie", "PPER"),
+                        ("hat", "haben", "VAFIN"),
+                        ("sich", "sich", "PRF"),
+                        ("über", "über", "APPR"),
+                        ("den", "der", "ART"),
+                        ("Vorschlag", "vorschlag", "NN"),
+                        ("von", "von", "APPR"),
+                        (ne_form, ne_lemma, "NE"),
+                        ("aufgeregt", "aufregen", "VVPP"),
+                        (".", ".", "$."),
+                    ],
+                    &[
+                        (0, "ROOT", 2),
+                        (2, "SUBJ", 1),
+                        (2, "AUX", 9),
+                        (9, "OBJA", 3),
+                        (9, "PP", 4),
+                        (4, "PN", 6),
+                        (6, "DET", 5),
+                        (6, "PP", 7),
+                        (7, "PN", 8),
+                        (2, "-PUNCT-", 10),
+                    ],
+                ),
+                _ => sentence(
+                    &[
+                        ("Einige", "einige", "PIS"),
+                        ("wollen", "wollen", "VMFIN"),
+                        ("euch", "ihr", "PPER"),
+                        ("alle", "alle", "PIAT"),
+                        ("ihre", "ihr", "PPOSAT"),
+                        ("Bücher", "buch", "NN"),
+                        ("zurückzugeben", "geben", "VVIZU"),
+                        (",", ",", "$,"),
+                        ("sagt", "sagen", "VVFIN"),
+                        (ne_form, ne_lemma, "NE"),
+                        (".", ".", "$."),
+                    ],
+                    &[
+                        (0, "ROOT", 9),
+                        (9, "OBJI", 2),
+                        (2, "SUBJ", 1),
+                        (2, "AUX", 7),
+                        (7, "OBJD", 3),
+                        (7, "OBJA", 6),
+                        (6, "DET", 4),
+                        (6, "DET", 5),
+                        (9, "-PUNCT-", 8),
+                        (9, "SUBJ", 10),
+                        (9, "-PUNCT-", 11),
+                    ],
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Construct a corpus from the test cases in the testdata directory.
+///
+/// Every test case becomes a graph, consisting of the token under test,
+/// its head (if any) and its dependents.
+fn testdata_corpus() -> Vec<TestGraph> {
+    let mut paths: Vec<_> = fs::read_dir("testdata")
+        .expect("Cannot read testdata directory")
+        .map(|entry| entry.expect("Cannot read directory entry").path())
+        .filter(|path| path.extension().map(|ext| ext == "test").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut corpus = Vec::new();
+    for path in paths {
+        let f = File::open(&path).expect("Cannot open test cases");
+        let test_cases = read_test_cases(BufReader::new(f))
+            .unwrap_or_else(|err| panic!("Cannot read {}: {}", path.display(), err));
+        corpus.extend(test_cases.into_iter().map(TestGraph::from));
+    }
+
+    corpus
+}
+
+/// A corpus graph that can be transformed.
+trait CorpusGraph: Clone {
+    fn graph(&self) -> &dyn DependencyGraph;
+
+    fn graph_mut(&mut self) -> &mut dyn DependencyGraph;
+}
+
+impl CorpusGraph for Sentence {
+    fn graph(&self) -> &dyn DependencyGraph {
+        self
+    }
+
+    fn graph_mut(&mut self) -> &mut dyn DependencyGraph {
+        self
+    }
+}
+
+impl CorpusGraph for TestGraph {
+    fn graph(&self) -> &dyn DependencyGraph {
+        TestGraph::graph(self)
+    }
+
+    fn graph_mut(&mut self) -> &mut dyn DependencyGraph {
+        TestGraph::graph_mut(self)
+    }
+}
+
+fn all_transforms() -> Vec<(&'static str, Box<dyn Transform>)> {
+    vec![
+        (
+            "AddContextualReflexiveTag",
+            Box::new(AddContextualReflexiveTag::new(ReflexiveConfidence::Low)),
+        ),
+        ("AddReflexiveTag", Box::new(AddReflexiveTag)),
+        (
+            "AddSeparableVerbPrefixes",
+            Box::new(AddSeparableVerbPrefixes::new(true)),
+        ),
+        (
+            "AddSeparatedVerbPrefix",
+            Box::new(AddSeparatedVerbPrefix::new(true)),
+        ),
+        (
+            "AddSeparatedVerbPrefixLinear",
+            Box::new(AddSeparatedVerbPrefixLinear::new(true)),
+        ),
+        ("FormAsLemma", Box::new(FormAsLemma)),
+        ("MarkVerbPrefix", Box::new(MarkVerbPrefix::new())),
+        ("RemoveAlternatives", Box::new(RemoveAlternatives)),
+        ("RemoveReflexiveTag", Box::new(RemoveReflexiveTag)),
+        ("RemoveSepVerbPrefix", Box::new(RemoveSepVerbPrefix)),
+        (
+            "RemoveSeparatedVerbPrefix",
+            Box::new(RemoveSeparatedVerbPrefix),
+        ),
+        ("RemoveTruncMarker", Box::new(RemoveTruncMarker)),
+        ("RestoreCase", Box::new(RestoreCase)),
+        ("SimplifyArticleLemma", Box::new(SimplifyArticleLemma)),
+        ("SimplifyPIAT", Box::new(SimplifyPIAT)),
+        ("SimplifyPIDAT", Box::new(SimplifyPIDAT)),
+        ("SimplifyPIS", Box::new(SimplifyPIS)),
+        (
+            "SimplifyPersonalPronounLemma",
+            Box::new(SimplifyPersonalPronounLemma),
+        ),
+        (
+            "SimplifyPossesivePronounLemma",
+            Box::new(SimplifyPossesivePronounLemma),
+        ),
+    ]
+}
+
+fn transform_corpus<G>(transform: &dyn Transform, corpus: &[G])
+where
+    G: CorpusGraph,
+{
+    for graph in corpus {
+        let graph = graph.graph();
+        for idx in 1..graph.len() {
+            black_box(transform.transform(graph, idx));
+        }
+    }
+}
+
+fn transforms(c: &mut Criterion) {
+    let synthetic = synthetic_corpus();
+    let testdata = testdata_corpus();
+
+    for (name, transform) in all_transforms() {
+        let mut group = c.benchmark_group(name);
+        group.bench_function("synthetic", |b| {
+            b.iter(|| transform_corpus(transform.as_ref(), &synthetic))
+        });
+        group.bench_function("testdata", |b| {
+            b.iter(|| transform_corpus(transform.as_ref(), &testdata))
+        });
+        group.finish();
+    }
+}
+
+fn restore_named_entity_case(c: &mut Criterion) {
+    // Only retain named entities, so that the benchmark measures the
+    // alignment of named entity forms and lemmas.
+    let corpus: Vec<_> = NAMED_ENTITIES
+        .iter()
+        .map(|&(form, lemma)| sentence(&[(form, lemma, "NE")], &[]))
+        .collect();
+
+    c.bench_function("restore_named_entity_case", |b| {
+        b.iter(|| transform_corpus(&RestoreCase, &corpus))
+    });
+}
+
+fn pipelines(c: &mut Criterion) {
+    let lemmatization = Transforms::new(pipeline::lemmatization());
+    let delemmatization = Transforms::new(pipeline::delemmatization());
+
+    // The synthetic corpus has predicted lemmas. Lemmatize it to get a
+    // corpus with TüBa-D/Z lemmas for the preprocessing pipeline.
+    let synthetic = synthetic_corpus();
+    let mut synthetic_tueba = synthetic.clone();
+    for sentence in &mut synthetic_tueba {
+        lemmatization.transform(sentence);
+    }
+
+    let testdata = testdata_corpus();
+
+    let mut group = c.benchmark_group("pipeline");
+    bench_pipeline(
+        &mut group,
+        "preproc/synthetic",
+        &delemmatization,
+        &synthetic_tueba,
+    );
+    bench_pipeline(&mut group, "preproc/testdata", &delemmatization, &testdata);
+    bench_pipeline(&mut group, "postproc/synthetic", &lemmatization, &synthetic);
+    bench_pipeline(&mut group, "postproc/testdata", &lemmatization, &testdata);
+    group.finish();
+}
+
+fn bench_pipeline<G>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    transforms: &Transforms,
+    corpus: &[G],
+) where
+    G: CorpusGraph,
+{
+    group.bench_function(name, |b| {
+        b.iter_batched(
+            || corpus.to_vec(),
+            |mut corpus| {
+                for graph in &mut corpus {
+                    transforms.transform(graph.graph_mut());
+                }
+                corpus
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, transforms, restore_named_entity_case, pipelines);
+criterion_main!(benches);
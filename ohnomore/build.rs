@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use fst::SetBuilder;
+
+#[path = "src/transform/lexicon_entry.rs"]
+mod lexicon_entry;
+
+use lexicon_entry::{compile_entry, expand_line};
+
+/// Bundled word lists that are compiled to FST sets.
+///
+/// The compiled set of `data/NAME.txt` is written to `$OUT_DIR/NAME.fst`
+/// and embedded with `bundled_fst!("NAME")`.
+const SETS: &[&str] = &[
+    "attr-poss-pronoun-prefixes",
+    "inseparable-verbs",
+    "optionally-reflexive-verbs",
+    "piat-prefixes",
+    "pidat-long-prefixes",
+    "pidat-prefixes",
+    "pis-long-prefixes",
+    "pis-prefixes",
+    "reflexive-verbs",
+    "subst-poss-pronoun-prefixes",
+    "tdz11-separable-prefixes",
+];
+
+/// Bundled lexicon of separable verbs.
+///
+/// The lexicon is compiled to a set of lexicon entries in
+/// `$OUT_DIR/NAME.fst` and a set of the verbs that occur with separable
+/// prefixes in `$OUT_DIR/NAME-base.fst`. Verbs that are also in the list
+/// of inseparable verbs are not added to the entries.
+const SEPARABLE_VERBS: &str = "tdz10-separable-verbs";
+
+/// Separable prefixes of the release of `SEPARABLE_VERBS`.
+///
+/// There is no list of separable prefixes for this release, so the set
+/// `$OUT_DIR/NAME.fst` is compiled from the prefixes that are marked in
+/// the lexicon of separable verbs.
+const SEPARABLE_VERB_PREFIXES: &str = "tdz10-separable-prefixes";
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+
+    for name in SETS {
+        let words = read_word_list(name);
+        write_set(&out_dir, name, words);
+    }
+
+    let inseparable_verbs = read_word_list("inseparable-verbs");
+
+    let path = format!("data/{}.txt", SEPARABLE_VERBS);
+    let data = read_data(&path);
+
+    // Later entries for a verb replace earlier entries, as in
+    // `lexicon::read_separable_verbs`.
+    let mut verbs = BTreeMap::new();
+    for (idx, line) in data.lines().enumerate() {
+        let entries = expand_line(line).unwrap_or_else(|reason| {
+            panic!(
+                "Invalid lexicon entry on line {} of {}: {}",
+                idx + 1,
+                path,
+                reason
+            )
+        });
+        verbs.extend(entries);
+    }
+
+    // The base verbs are the verbs that are attested with a separable
+    // prefix, e.g. *fangen* for *an#fangen*.
+    let base_verbs = verbs
+        .values()
+        .filter_map(|lemma| lemma.rsplit('#').next())
+        .map(str::to_lowercase)
+        .collect::<BTreeSet<_>>();
+    write_set(&out_dir, &format!("{}-base", SEPARABLE_VERBS), base_verbs);
+
+    // Prefixes with an elided *her* or *hin* are also attached to forms
+    // without the apostrophe, e.g. *rüberbringen*.
+    let prefixes = verbs
+        .values()
+        .flat_map(|lemma| {
+            let mut parts = lemma.split('#').collect::<Vec<_>>();
+            parts.pop();
+            parts
+        })
+        .map(|prefix| prefix.trim_start_matches('\'').to_lowercase())
+        .collect::<BTreeSet<_>>();
+    write_set(&out_dir, SEPARABLE_VERB_PREFIXES, prefixes);
+
+    // Verbs with an inseparable reading are disambiguated by
+    // `MarkVerbPrefix`, rather than looked up.
+    let entries = verbs
+        .iter()
+        .filter(|(verb, _)| !inseparable_verbs.contains(verb.as_str()))
+        .map(|(verb, lemma)| compile_entry(verb, lemma))
+        .collect::<BTreeSet<_>>();
+    write_set(&out_dir, SEPARABLE_VERBS, entries);
+}
+
+fn read_data(path: &str) -> String {
+    println!("cargo:rerun-if-changed={}", path);
+    fs::read_to_string(path).unwrap_or_else(|err| panic!("Cannot read {}: {}", path, err))
+}
+
+/// Read `data/NAME.txt`, a word list with one word per line.
+fn read_word_list(name: &str) -> BTreeSet<String> {
+    read_data(&format!("data/{}.txt", name))
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Compile words to the FST set `$OUT_DIR/NAME.fst`.
+///
+/// The set builder requires sorted, unique keys, which a `BTreeSet`
+/// provides.
+fn write_set(out_dir: &str, name: &str, words: BTreeSet<String>) {
+    let fst_path = Path::new(out_dir).join(format!("{}.fst", name));
+    let writer = BufWriter::new(
+        File::create(&fst_path)
+            .unwrap_or_else(|err| panic!("Cannot create {}: {}", fst_path.display(), err)),
+    );
+    let mut builder = SetBuilder::new(writer).expect("Cannot create set builder");
+    builder
+        .extend_iter(words)
+        .unwrap_or_else(|err| panic!("Cannot compile {}: {}", name, err));
+    builder
+        .finish()
+        .unwrap_or_else(|err| panic!("Cannot write {}: {}", fst_path.display(), err));
+}
@@ -1,3 +1,5 @@
+//! Prefix and suffix search in FST-based sets.
+
 use fst::raw::{Fst, Node};
 use fst::Set;
 
@@ -70,17 +72,187 @@ where
     }
 }
 
+/// Search sequences of prefixes of a string in a set.
+pub trait PrefixStar {
+    /// Enumerate the matches of *(prefix)\** at the start of a string.
+    ///
+    /// This is equivalent to searching prefixes of the string using the
+    /// Kleene star of the set. For every match, `f` is called with the end
+    /// offsets of the prefixes in the match. The first match is the empty
+    /// match. Matches are enumerated depth-first, so that the offsets of
+    /// matches with the same number of prefixes are enumerated in
+    /// lexicographic order.
+    ///
+    /// The offsets are stored in a single buffer, so no allocations are
+    /// necessary for individual matches.
+    fn prefix_star<F>(&self, word: &str, f: F)
+    where
+        F: FnMut(&[usize]);
+}
+
+impl<D> PrefixStar for Set<D>
+where
+    D: AsRef<[u8]>,
+{
+    fn prefix_star<F>(&self, word: &str, mut f: F)
+    where
+        F: FnMut(&[usize]),
+    {
+        let mut offsets = Vec::new();
+        f(&offsets);
+        prefix_star_from(self.as_fst(), word.as_bytes(), &mut offsets, &mut f);
+    }
+}
+
+/// Enumerate the matches of *(prefix)+* that start at the last offset.
+fn prefix_star_from<D, F>(fst: &Fst<D>, word: &[u8], offsets: &mut Vec<usize>, f: &mut F)
+where
+    D: AsRef<[u8]>,
+    F: FnMut(&[usize]),
+{
+    let start = offsets.last().cloned().unwrap_or(0);
+    let mut node = fst.root();
+
+    for (len, &byte) in word[start..].iter().enumerate() {
+        node = match node.find_input(byte) {
+            Some(trans_idx) => fst.node(node.transition(trans_idx).addr),
+            None => return,
+        };
+
+        // When a final state is reached, we have found a prefix. The
+        // match can be extended by starting a new prefix (the Kleene
+        // star), or by continuing the current prefix.
+        if node.is_final() {
+            offsets.push(start + len + 1);
+            f(offsets);
+            prefix_star_from(fst, word, offsets, f);
+            offsets.pop();
+        }
+    }
+}
+
+/// Set that supports suffix searches.
+///
+/// The strings are stored reversed in an FST, so that the suffixes of a
+/// string can be found by traversing the FST from the end of the string.
+pub struct SuffixSet<D>(Set<D>);
+
+impl SuffixSet<Vec<u8>> {
+    /// Construct a suffix set from an iterator over strings.
+    ///
+    /// In contrast to `Set::from_iter`, the strings do not have to be
+    /// in lexicographic order.
+    pub fn from_iter<T, I>(iter: I) -> Result<Self, fst::Error>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = T>,
+    {
+        let mut reversed: Vec<Vec<u8>> = iter
+            .into_iter()
+            .map(|s| s.as_ref().bytes().rev().collect())
+            .collect();
+        reversed.sort();
+        reversed.dedup();
+
+        Ok(SuffixSet(Set::from_iter(reversed)?))
+    }
+}
+
+impl<D> SuffixSet<D>
+where
+    D: AsRef<[u8]>,
+{
+    /// Check whether a string is in the set.
+    pub fn contains(&self, word: &str) -> bool {
+        self.suffixes(word).last().map(str::len) == Some(word.len())
+    }
+}
+
+/// Search suffixes of a string in a set.
+pub trait Suffixes<D> {
+    /// Get an iterator over the suffixes of a string that are in a set.
+    ///
+    /// The suffixes are returned from short to long.
+    fn suffixes<'a, 'b>(&'a self, word: &'b str) -> SuffixIter<'a, 'b, D>;
+}
+
+impl<D> Suffixes<D> for SuffixSet<D>
+where
+    D: AsRef<[u8]>,
+{
+    fn suffixes<'a, 'b>(&'a self, word: &'b str) -> SuffixIter<'a, 'b, D> {
+        SuffixIter {
+            fst: self.0.as_fst(),
+            node: self.0.as_fst().root(),
+            suffix_len: 0,
+            word,
+        }
+    }
+}
+
+/// Suffix iterator.
+pub struct SuffixIter<'a, 'b, D> {
+    fst: &'a Fst<D>,
+    node: Node<'a>,
+    suffix_len: usize,
+    word: &'b str,
+}
+
+impl<'a, 'b, D> Iterator for SuffixIter<'a, 'b, D>
+where
+    D: AsRef<[u8]>,
+{
+    type Item = &'b str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.word.as_bytes();
+
+        while self.suffix_len < bytes.len() {
+            match self
+                .node
+                .find_input(bytes[bytes.len() - self.suffix_len - 1])
+            {
+                Some(trans_idx) => {
+                    let trans = self.node.transition(trans_idx);
+                    self.node = self.fst.node(trans.addr);
+                    self.suffix_len += 1;
+                }
+                None => return None,
+            };
+
+            if self.node.is_final() {
+                return Some(&self.word[bytes.len() - self.suffix_len..]);
+            }
+        }
+
+        None
+    }
+}
+
+/// Search the longest suffix of a string in a set.
+pub trait LongestSuffix {
+    /// Search the longest suffix of a string in a set.
+    fn longest_suffix<'a>(&self, word: &'a str) -> Option<&'a str>;
+}
+
+impl<D> LongestSuffix for SuffixSet<D>
+where
+    D: AsRef<[u8]>,
+{
+    fn longest_suffix<'a>(&self, word: &'a str) -> Option<&'a str> {
+        self.suffixes(word).last()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fst::{Set, SetBuilder};
 
-    use super::Prefixes;
+    use super::{LongestSuffix, PrefixStar, Prefixes, SuffixSet, Suffixes};
 
     fn test_set() -> Set<Vec<u8>> {
         let mut builder = SetBuilder::memory();
-        builder
-            .extend_iter(&["p", "pre", "pref", "prefix"])
-            .unwrap();
+        builder.extend_iter(["p", "pre", "pref", "prefix"]).unwrap();
         let bytes = builder.into_inner().unwrap();
         Set::new(bytes).unwrap()
     }
@@ -103,4 +275,79 @@ mod tests {
 
         assert!(set.prefixes("fix").next().is_none());
     }
+
+    #[test]
+    fn finds_prefix_star() {
+        let set = Set::from_iter(["a", "ab", "b", "ba"]).unwrap();
+
+        let mut matches = Vec::new();
+        set.prefix_star("abab", |offsets| matches.push(offsets.to_owned()));
+
+        assert_eq!(
+            matches,
+            vec![
+                vec![],
+                vec![1],
+                vec![1, 2],
+                vec![1, 2, 3],
+                vec![1, 2, 3, 4],
+                vec![1, 2, 4],
+                vec![1, 3],
+                vec![1, 3, 4],
+                vec![2],
+                vec![2, 3],
+                vec![2, 3, 4],
+                vec![2, 4],
+            ]
+        );
+
+        let mut matches = Vec::new();
+        set.prefix_star("cab", |offsets| matches.push(offsets.to_owned()));
+        assert_eq!(matches, vec![Vec::<usize>::new()]);
+    }
+
+    fn test_suffix_set() -> SuffixSet<Vec<u8>> {
+        SuffixSet::from_iter(vec!["es", "xes", "fixes", "prefixes", "äß"]).unwrap()
+    }
+
+    #[test]
+    fn finds_suffixes() {
+        let set = test_suffix_set();
+
+        let mut iter = set.suffixes("prefixes");
+        assert_eq!(iter.next(), Some("es"));
+        assert_eq!(iter.next(), Some("xes"));
+        assert_eq!(iter.next(), Some("fixes"));
+        assert_eq!(iter.next(), Some("prefixes"));
+        assert!(iter.next().is_none());
+
+        let mut iter = set.suffixes("xes");
+        assert_eq!(iter.next(), Some("es"));
+        assert_eq!(iter.next(), Some("xes"));
+        assert!(iter.next().is_none());
+
+        assert!(set.suffixes("fix").next().is_none());
+
+        let mut iter = set.suffixes("Fäß");
+        assert_eq!(iter.next(), Some("äß"));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn finds_longest_suffix() {
+        let set = test_suffix_set();
+
+        assert_eq!(set.longest_suffix("affixes"), Some("fixes"));
+        assert_eq!(set.longest_suffix("boxes"), Some("xes"));
+        assert_eq!(set.longest_suffix("box"), None);
+    }
+
+    #[test]
+    fn suffix_set_contains() {
+        let set = test_suffix_set();
+
+        assert!(set.contains("fixes"));
+        assert!(!set.contains("ixes"));
+        assert!(!set.contains("affixes"));
+    }
 }
@@ -1,7 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use lazy_static::lazy_static;
-use maplit::hashset;
+use maplit::{hashmap, hashset};
 
 pub(crate) static REFLEXIVE_PERSONAL_PRONOUN_LEMMA: &str = "#refl";
 
@@ -9,10 +9,17 @@ pub(crate) static SEPARABLE_PARTICLE_POS: &str = "PTKVZ";
 
 pub(crate) static PUNCTUATION_PREFIX: &str = "$";
 
+pub(crate) static ACCUSATIVE_OBJECT_RELATION: &str = "OBJA";
+pub(crate) static AUXILIARY_RELATION: &str = "AUX";
+pub(crate) static COORDINATION_RELATION: &str = "KON";
+pub(crate) static DATIVE_OBJECT_RELATION: &str = "OBJD";
+pub(crate) static SUBJECT_RELATION: &str = "SUBJ";
+
 pub(crate) static ARTICLE_TAG: &str = "ART";
 pub(crate) static ATTRIBUTIVE_POSSESIVE_PRONOUN_TAG: &str = "PPOSAT";
 pub(crate) static SUBST_POSSESIVE_PRONOUN_TAG: &str = "PPOSS";
 pub(crate) static FOREIGN_WORD_TAG: &str = "FM";
+pub(crate) static IMPERATIVE_VERB_TAG: &str = "VVIMP";
 pub(crate) static NAMED_ENTITY_TAG: &str = "NE";
 pub(crate) static NON_WORD_TAG: &str = "XY";
 pub(crate) static NOUN_TAG: &str = "NN";
@@ -27,6 +34,24 @@ pub(crate) static SUBSTITUTING_INDEF_PRONOUN: &str = "PIS";
 pub(crate) static ATTRIBUTING_INDEF_PRONOUN_WITHOUT_DET: &str = "PIAT";
 pub(crate) static ATTRIBUTING_INDEF_PRONOUN_WITH_DET: &str = "PIDAT";
 
+pub(crate) static FINITE_VERB_TAGS: [&str; 4] = ["VAFIN", "VMFIN", "VVFIN", "VVIMP"];
+
+pub(crate) static CLAUSE_BOUNDARY_TAGS: [&str; 3] = ["$,", "$.", "KOUS"];
+
+pub(crate) static PARTICIPLE_TAGS: [&str; 3] = ["VAPP", "VMPP", "VVPP"];
+
+pub(crate) static SEPARABLE_VERB_TAGS: [&str; 5] = ["VAFIN", "VMFIN", "VVFIN", "VVIMP", "VVPP"];
+
+/// Prefixes that are separable in some verbs and inseparable in others,
+/// e.g. *über#setzen* and *übersetzen*.
+pub(crate) static AMBIGUOUS_VERB_PREFIXES: [&str; 7] =
+    ["durch", "über", "um", "unter", "voll", "wider", "wieder"];
+
+/// Unstressed verb prefixes, verbs with these prefixes form participles
+/// without *ge-*.
+pub(crate) static UNSTRESSED_VERB_PREFIXES: [&str; 7] =
+    ["be", "emp", "ent", "er", "miss", "ver", "zer"];
+
 lazy_static! {
     pub(crate) static ref LEMMA_IS_FORM_TAGS: HashSet<&'static str> = hashset! {
         "$,",
@@ -47,6 +72,18 @@ lazy_static! {
         "PTKA",
         "PTKNEG",
     };
+    pub(crate) static ref SECOND_PERSON_SUBJECTS: HashSet<&'static str> = hashset! {
+        "du",
+        "ihr",
+    };
+    pub(crate) static ref REFLEXIVE_PERSONAL_PRONOUN_SUBJECTS: HashMap<&'static str, &'static str> = hashmap! {
+        "mich" => "ich",
+        "mir" => "ich",
+        "dich" => "du",
+        "dir" => "du",
+        "uns" => "wir",
+        "euch" => "ihr",
+    };
     pub(crate) static ref LEMMA_IS_FORM_PRESERVE_CASE_TAGS: HashSet<&'static str> = hashset! {
         FOREIGN_WORD_TAG,
     };
@@ -63,6 +100,5 @@ pub(crate) fn is_separable_verb<S>(tag: S) -> bool
 where
     S: AsRef<str>,
 {
-    let tag = tag.as_ref();
-    tag == "VVFIN" || tag == "VVPP" || tag == "VVIMP" || tag == "VMFIN" || tag == "VAFIN"
+    SEPARABLE_VERB_TAGS.contains(&tag.as_ref())
 }
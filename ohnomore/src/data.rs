@@ -0,0 +1,227 @@
+//! Bundled data for TüBa-D/Z releases.
+//!
+//! The lexicons that transformations use depend on the TüBa-D/Z release
+//! that the lemmas follow. `DataBundle` provides the bundled lexicons for
+//! a release.
+//!
+//! The lexicons are compiled to FSTs by the build script, so that they
+//! are validated at build time and can be used without construction cost.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use fst::Set;
+
+use crate::transform::lexicon::SeparableVerbs;
+use crate::LemmatizationError;
+
+/// Embed a set that was compiled by the build script.
+///
+/// Evaluates to the bytes of `$OUT_DIR/NAME.fst`, which is compiled from
+/// `data/NAME.txt`. The bytes can be loaded with `bundled_set`.
+macro_rules! bundled_fst {
+    ($name:literal) => {
+        &include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".fst"))[..]
+    };
+}
+
+pub(crate) use bundled_fst;
+
+/// Load a set that was compiled by the build script.
+///
+/// Loading does not copy the set. Since the build script verified that
+/// the word list can be compiled, loading only fails on a corrupt build.
+pub(crate) fn bundled_set<D>(fst: &'static [u8]) -> Set<D>
+where
+    D: AsRef<[u8]> + From<&'static [u8]>,
+{
+    Set::new(D::from(fst)).expect("Compiled set is corrupt")
+}
+
+/// A TüBa-D/Z release.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TuebaRelease {
+    /// TüBa-D/Z release 10.
+    Tdz10,
+
+    /// TüBa-D/Z release 11.
+    Tdz11,
+}
+
+impl TuebaRelease {
+    /// The releases that have bundled data.
+    pub fn releases() -> &'static [TuebaRelease] {
+        &[TuebaRelease::Tdz10, TuebaRelease::Tdz11]
+    }
+}
+
+impl Default for TuebaRelease {
+    /// The most recent release.
+    fn default() -> Self {
+        TuebaRelease::Tdz11
+    }
+}
+
+impl fmt::Display for TuebaRelease {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuebaRelease::Tdz10 => write!(f, "10"),
+            TuebaRelease::Tdz11 => write!(f, "11"),
+        }
+    }
+}
+
+impl FromStr for TuebaRelease {
+    type Err = LemmatizationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "10" => Ok(TuebaRelease::Tdz10),
+            "11" => Ok(TuebaRelease::Tdz11),
+            _ => Err(LemmatizationError::UnknownRelease(s.to_owned())),
+        }
+    }
+}
+
+/// Bundled lexicons of a TüBa-D/Z release.
+///
+/// The releases differ in their separable prefixes. Release 11 uses the
+/// list of separable prefixes of release 11. Release 10 uses the prefixes
+/// that are marked in its lexicon of separable verbs, which also contains
+/// adjectives and adverbs that release 11 does not treat as prefixes,
+/// e.g. *gut* in *gut#machen*.
+///
+/// Only the separable verbs of release 10 are bundled, release 11 uses
+/// the same lexicon. The other lexicons are not release-specific.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DataBundle {
+    release: TuebaRelease,
+    separable_prefixes: &'static [u8],
+    separable_verbs: &'static [u8],
+    separable_base_verbs: &'static [u8],
+    inseparable_verbs: &'static [u8],
+    reflexive_verbs: &'static [u8],
+    optionally_reflexive_verbs: &'static [u8],
+}
+
+impl DataBundle {
+    /// Get the bundled data for a release.
+    pub fn for_release(release: TuebaRelease) -> Self {
+        let separable_prefixes = match release {
+            TuebaRelease::Tdz10 => bundled_fst!("tdz10-separable-prefixes"),
+            TuebaRelease::Tdz11 => bundled_fst!("tdz11-separable-prefixes"),
+        };
+
+        DataBundle {
+            release,
+            separable_prefixes,
+            separable_verbs: bundled_fst!("tdz10-separable-verbs"),
+            separable_base_verbs: bundled_fst!("tdz10-separable-verbs-base"),
+            inseparable_verbs: bundled_fst!("inseparable-verbs"),
+            reflexive_verbs: bundled_fst!("reflexive-verbs"),
+            optionally_reflexive_verbs: bundled_fst!("optionally-reflexive-verbs"),
+        }
+    }
+
+    /// Get the release of the data.
+    pub fn release(&self) -> TuebaRelease {
+        self.release
+    }
+
+    /// Separable verb prefixes.
+    pub fn separable_prefixes(&self) -> Set<Cow<'static, [u8]>> {
+        bundled_set(self.separable_prefixes)
+    }
+
+    /// Separable verbs.
+    ///
+    /// Verbs that are in the lexicon of inseparable verbs are excluded.
+    pub fn separable_verbs(&self) -> SeparableVerbs {
+        SeparableVerbs::from_entries(bundled_set(self.separable_verbs))
+    }
+
+    /// Lowercased verbs that are attested with a separable prefix.
+    pub fn separable_base_verbs(&self) -> Set<Cow<'static, [u8]>> {
+        bundled_set(self.separable_base_verbs)
+    }
+
+    /// Lowercased inseparable verbs with an ambiguous prefix.
+    pub fn inseparable_verbs(&self) -> Set<Cow<'static, [u8]>> {
+        bundled_set(self.inseparable_verbs)
+    }
+
+    /// Lowercased inherently reflexive verbs.
+    pub fn reflexive_verbs(&self) -> Set<Cow<'static, [u8]>> {
+        bundled_set(self.reflexive_verbs)
+    }
+
+    /// Lowercased optionally reflexive verbs.
+    pub fn optionally_reflexive_verbs(&self) -> Set<Cow<'static, [u8]>> {
+        bundled_set(self.optionally_reflexive_verbs)
+    }
+}
+
+impl Default for DataBundle {
+    fn default() -> Self {
+        DataBundle::for_release(TuebaRelease::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataBundle, TuebaRelease};
+
+    #[test]
+    pub fn releases_can_be_parsed() {
+        for &release in TuebaRelease::releases() {
+            assert_eq!(
+                release.to_string().parse::<TuebaRelease>().unwrap(),
+                release
+            );
+        }
+
+        assert!("9".parse::<TuebaRelease>().is_err());
+    }
+
+    #[test]
+    pub fn bundled_data_is_compiled() {
+        for &release in TuebaRelease::releases() {
+            let data = DataBundle::for_release(release);
+            assert_eq!(data.release(), release);
+            assert!(data.separable_prefixes().contains("ab"));
+            assert_eq!(
+                data.separable_verbs().get("abarbeiten").as_deref(),
+                Some("ab#arbeiten")
+            );
+            assert_eq!(
+                data.separable_verbs().get("'rüberbringen").as_deref(),
+                Some("'rüber#bringen")
+            );
+            assert!(data.separable_base_verbs().contains("arbeiten"));
+            assert!(data.inseparable_verbs().contains("durchkreuzen"));
+            assert!(data.reflexive_verbs().contains("anfreunden"));
+            assert!(data.optionally_reflexive_verbs().contains("abfinden"));
+        }
+    }
+
+    #[test]
+    pub fn releases_have_different_prefixes() {
+        let tdz10 = DataBundle::for_release(TuebaRelease::Tdz10);
+        let tdz11 = DataBundle::for_release(TuebaRelease::Tdz11);
+        assert_ne!(tdz10, tdz11);
+
+        assert!(tdz10.separable_prefixes().contains("gut"));
+        assert!(!tdz11.separable_prefixes().contains("gut"));
+
+        // Elided prefixes are also added without the apostrophe.
+        assert!(tdz10.separable_prefixes().contains("rüber"));
+    }
+
+    #[test]
+    pub fn inseparable_verbs_are_not_looked_up() {
+        assert!(!DataBundle::default()
+            .separable_verbs()
+            .contains("übersetzen"));
+    }
+}
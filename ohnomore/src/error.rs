@@ -9,4 +9,13 @@ pub enum LemmatizationError {
 
     #[error(transparent)]
     Fst(#[from] fst::Error),
+
+    #[error("Invalid lexicon entry on line {line}: {reason}")]
+    InvalidLexiconEntry { line: usize, reason: String },
+
+    #[error("Invalid test case on line {line}: {reason}")]
+    InvalidTestCase { line: usize, reason: String },
+
+    #[error("Unknown TüBa-D/Z release: {0}")]
+    UnknownRelease(String),
 }
@@ -1,7 +1,9 @@
-mod automaton;
+pub mod automaton;
 
 pub mod constants;
 
+pub mod data;
+
 mod error;
 pub use error::LemmatizationError;
 
@@ -0,0 +1,194 @@
+use std::cell::OnceCell;
+
+use super::{DependencyGraph, Token, TokenMut};
+
+/// A dependency graph with a precomputed adjacency index.
+///
+/// Transformations only change lemmas, so the dependency structure of a
+/// graph stays the same while a transformation list is applied. This
+/// wrapper reads the structure once, on the first dependency lookup, and
+/// answers all further lookups from the index rather than querying the
+/// wrapped graph. Graphs are not indexed when no transformation uses
+/// dependency relations.
+pub(crate) struct IndexedGraph<'a> {
+    graph: &'a mut dyn DependencyGraph,
+    adjacency: OnceCell<Adjacency>,
+}
+
+impl<'a> IndexedGraph<'a> {
+    pub(crate) fn new(graph: &'a mut dyn DependencyGraph) -> Self {
+        IndexedGraph {
+            graph,
+            adjacency: OnceCell::new(),
+        }
+    }
+
+    fn adjacency(&self) -> &Adjacency {
+        self.adjacency.get_or_init(|| Adjacency::new(&*self.graph))
+    }
+}
+
+impl<'g> DependencyGraph for IndexedGraph<'g> {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        let adjacency = self.adjacency();
+        Box::new(
+            adjacency.dependents[adjacency.offsets[idx]..adjacency.offsets[idx + 1]]
+                .iter()
+                .map(move |&(dependent, label)| (dependent, adjacency.labels[label].as_str())),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        let adjacency = self.adjacency();
+        adjacency.heads[idx].map(|(head, label)| (head, adjacency.labels[label].as_str()))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        self.graph.token(idx)
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        self.graph.token_mut(idx)
+    }
+
+    fn len(&self) -> usize {
+        self.graph.len()
+    }
+}
+
+/// Adjacency index of a dependency graph.
+///
+/// Relation labels are interned, so that each distinct label is stored
+/// once per sentence.
+struct Adjacency {
+    /// The dependents of node `i` are stored in
+    /// `dependents[offsets[i]..offsets[i + 1]]`.
+    offsets: Vec<usize>,
+
+    /// Dependents with the indices of their relation labels.
+    dependents: Vec<(usize, usize)>,
+
+    /// Heads with the indices of their relation labels.
+    heads: Vec<Option<(usize, usize)>>,
+
+    labels: Vec<String>,
+}
+
+impl Adjacency {
+    fn new(graph: &dyn DependencyGraph) -> Self {
+        let len = graph.len();
+        let mut offsets = Vec::with_capacity(len + 1);
+        let mut dependents = Vec::with_capacity(len);
+        let mut heads = vec![None; len];
+        let mut labels: Vec<String> = Vec::new();
+
+        offsets.push(0);
+        for head in 0..len {
+            for (dependent, relation) in graph.dependents(head) {
+                // Sentences only use a handful of distinct labels, so a
+                // linear search is cheaper than hashing.
+                let label = match labels.iter().position(|label| label == relation) {
+                    Some(label) => label,
+                    None => {
+                        labels.push(relation.to_owned());
+                        labels.len() - 1
+                    }
+                };
+
+                dependents.push((dependent, label));
+                heads[dependent] = Some((head, label));
+            }
+
+            offsets.push(dependents.len());
+        }
+
+        Adjacency {
+            offsets,
+            dependents,
+            heads,
+            labels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udgraph::graph::{DepTriple, Sentence};
+    use udgraph::token::TokenBuilder;
+
+    use super::IndexedGraph;
+    use crate::transform::DependencyGraph;
+
+    #[test]
+    pub fn index_agrees_with_graph() {
+        let mut sentence = Sentence::new();
+        for form in &["Er", "fängt", "heute", "wieder", "an", "."] {
+            sentence.push(TokenBuilder::new(*form).into());
+        }
+
+        let mut dep_graph = sentence.dep_graph_mut();
+        for &(head, relation, dependent) in &[
+            (0, "ROOT", 2),
+            (2, "SUBJ", 1),
+            (2, "ADV", 3),
+            (2, "ADV", 4),
+            (2, "AVZ", 5),
+            (5, "-PUNCT-", 6),
+        ] {
+            dep_graph
+                .add_deprel(DepTriple::new(head, Some(relation), dependent))
+                .unwrap();
+        }
+
+        let expected_dependents = (0..sentence.len())
+            .map(|idx| {
+                let mut dependents = sentence.dependents(idx).collect::<Vec<_>>();
+                dependents.sort_unstable();
+                dependents
+                    .into_iter()
+                    .map(|(dependent, relation)| (dependent, relation.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let expected_heads = (0..sentence.len())
+            .map(|idx| {
+                sentence
+                    .head(idx)
+                    .map(|(head, relation)| (head, relation.to_owned()))
+            })
+            .collect::<Vec<_>>();
+
+        let graph = IndexedGraph::new(&mut sentence);
+        assert_eq!(graph.adjacency().labels.len(), 5);
+
+        for idx in 0..graph.len() {
+            let mut dependents = graph.dependents(idx).collect::<Vec<_>>();
+            dependents.sort_unstable();
+            assert_eq!(
+                dependents,
+                expected_dependents[idx]
+                    .iter()
+                    .map(|(dependent, relation)| (*dependent, relation.as_str()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                graph.head(idx),
+                expected_heads[idx]
+                    .as_ref()
+                    .map(|(head, relation)| (*head, relation.as_str()))
+            );
+        }
+
+        assert_eq!(
+            graph.siblings(3).collect::<Vec<_>>(),
+            graph
+                .dependents(2)
+                .filter(|&(dependent, _)| dependent != 3)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            graph.ancestors(6).collect::<Vec<_>>(),
+            vec![(5, "-PUNCT-"), (2, "AVZ"), (0, "ROOT")]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Compound analysis.
+//!
+//! This module provides an analysis of compounds into their constituents,
+//! using a lexicon of words that can be constituents.
+
+use std::fmt;
+use std::io::BufRead;
+use std::iter;
+
+use fst::Set;
+
+use crate::automaton::{SuffixSet, Suffixes};
+use crate::constants::*;
+use crate::transform::{DependencyGraph, MiscTransform};
+use crate::LemmatizationError;
+
+/// The MISC feature that `AddCompoundSplit` adds.
+static COMPOUND_FEATURE: &str = "Compound";
+
+/// Linking elements that can occur between constituents.
+static LINKING_ELEMENTS: [&str; 3] = ["es", "n", "s"];
+
+/// The minimum length of a constituent in characters.
+const MIN_CONSTITUENT_LEN: usize = 3;
+
+/// A constituent of a compound.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constituent {
+    stem: String,
+    linking_element: Option<&'static str>,
+}
+
+impl Constituent {
+    /// Get the lowercased stem of the constituent.
+    pub fn stem(&self) -> &str {
+        &self.stem
+    }
+
+    /// Get the linking element that attaches this constituent to the next
+    /// constituent. For example, *es* in *Bundesregierung*.
+    pub fn linking_element(&self) -> Option<&str> {
+        self.linking_element
+    }
+}
+
+/// Analysis of a compound.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Compound {
+    constituents: Vec<Constituent>,
+}
+
+impl Compound {
+    /// Get the constituents of the compound.
+    pub fn constituents(&self) -> &[Constituent] {
+        &self.constituents
+    }
+
+    /// Get the head of the compound, which is its last constituent.
+    pub fn head(&self) -> &str {
+        &self
+            .constituents
+            .last()
+            .expect("Compound without constituents")
+            .stem
+    }
+}
+
+impl fmt::Display for Compound {
+    /// Format the compound as its constituents, separated by *+*. Linking
+    /// elements are added between parentheses, e.g. *bund(es)+regierung*.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, constituent) in self.constituents.iter().enumerate() {
+            if idx != 0 {
+                f.write_str("+")?;
+            }
+
+            f.write_str(&constituent.stem)?;
+
+            if let Some(linking_element) = constituent.linking_element {
+                write!(f, "({})", linking_element)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Split compounds into constituents.
+///
+/// Compounds are split into constituents that are in the lexicon. Between
+/// two constituents, one of the linking elements *-s-*, *-es-*, or *-n-*
+/// may occur. For example, *Straßenbahn* is split into *straße(n)+bahn*
+/// when *straße* and *bahn* are in the lexicon.
+///
+/// Since German compounds are right-headed, compounds are analyzed from
+/// right to left, by searching suffixes of the compound in the lexicon.
+/// When there are multiple analyses, the analysis with the fewest
+/// constituents is preferred. Among those analyses, the analysis with
+/// the longest head is preferred.
+pub struct CompoundSplitter {
+    lexicon: SuffixSet<Vec<u8>>,
+}
+
+impl CompoundSplitter {
+    /// Create a compound splitter from a set of lowercased words.
+    ///
+    /// Returns an error when the lexicon contains invalid UTF-8.
+    pub fn new(lexicon: Set<Vec<u8>>) -> Result<Self, LemmatizationError> {
+        let words = lexicon.stream().into_strs()?;
+
+        Ok(CompoundSplitter {
+            lexicon: SuffixSet::from_iter(words)?,
+        })
+    }
+
+    /// Read the lexicon from a buffered reader.
+    ///
+    /// The lexicon should contain one word per line. Words are lowercased.
+    pub fn read_lexicon<R>(r: R) -> Result<Self, LemmatizationError>
+    where
+        R: BufRead,
+    {
+        let mut words = Vec::new();
+
+        for line in r.lines() {
+            let line = line?;
+            let word = line.trim();
+
+            if !word.is_empty() {
+                words.push(word.to_lowercase());
+            }
+        }
+
+        Ok(CompoundSplitter {
+            lexicon: SuffixSet::from_iter(words)?,
+        })
+    }
+
+    /// Split a word into its constituents.
+    ///
+    /// Returns `None` when the word could not be split into at least two
+    /// constituents.
+    pub fn split(&self, word: &str) -> Option<Compound> {
+        let word = word.to_lowercase();
+
+        self.best_analysis(&word)
+            .map(|constituents| Compound { constituents })
+    }
+
+    /// Find the best analysis of a word with at least two constituents.
+    ///
+    /// The best analyses of the prefixes of the word are computed from
+    /// left to right. Each prefix analysis extends the best analysis of a
+    /// shorter prefix by one constituent, so the number of segmentations
+    /// that is considered is linear in the number of split points.
+    fn best_analysis(&self, word: &str) -> Option<Vec<Constituent>> {
+        // The best analysis of word[..end] is stored in best[end].
+        let mut best: Vec<Option<Vec<Constituent>>> = vec![None; word.len() + 1];
+
+        let ends = word
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .skip(1)
+            .chain(iter::once(word.len()));
+        for end in ends {
+            let prefix = &word[..end];
+
+            // Prefixes can be constituents, the word itself must be split.
+            let mut prefix_best = None;
+            if end != word.len() && is_constituent(prefix) && self.lexicon.contains(prefix) {
+                prefix_best = Some(vec![Constituent {
+                    stem: prefix.to_owned(),
+                    linking_element: None,
+                }]);
+            }
+
+            for head in self.lexicon.suffixes(prefix) {
+                let start = end - head.len();
+                if start == 0 || !is_constituent(head) {
+                    continue;
+                }
+
+                let linking_elements =
+                    iter::once(None).chain(LINKING_ELEMENTS.iter().cloned().map(Some));
+                for linking_element in linking_elements {
+                    let linking_element_len = linking_element.map(str::len).unwrap_or(0);
+                    if !word[..start].ends_with(linking_element.unwrap_or("")) {
+                        continue;
+                    }
+
+                    let rest = match &best[start - linking_element_len] {
+                        Some(rest) => rest,
+                        None => continue,
+                    };
+
+                    let is_better = match &prefix_best {
+                        Some(prefix_best) => is_better_analysis(rest.len() + 1, head, prefix_best),
+                        None => true,
+                    };
+                    if is_better {
+                        let mut constituents = rest.clone();
+                        constituents.last_mut().unwrap().linking_element = linking_element;
+                        constituents.push(Constituent {
+                            stem: head.to_owned(),
+                            linking_element: None,
+                        });
+                        prefix_best = Some(constituents);
+                    }
+                }
+            }
+
+            best[end] = prefix_best;
+        }
+
+        best.pop().flatten()
+    }
+}
+
+/// Check whether an analysis with `len` constituents and head `head` is
+/// better than `analysis`.
+///
+/// Analyses with fewer constituents are better. Among analyses with the
+/// same number of constituents, the analysis with the longest head is
+/// better.
+fn is_better_analysis(len: usize, head: &str, analysis: &[Constituent]) -> bool {
+    let analysis_head = &analysis.last().expect("Analysis without constituents").stem;
+    len < analysis.len() || (len == analysis.len() && head.len() > analysis_head.len())
+}
+
+fn is_constituent(word: &str) -> bool {
+    word.chars().count() >= MIN_CONSTITUENT_LEN
+}
+
+/// Add compound analyses of nouns to the MISC column.
+///
+/// This transformation splits the lemmas of nouns (NN) using a
+/// `CompoundSplitter`. The analysis is added as the *Compound* feature, e.g.
+/// *Compound=bund(es)+regierung* for *Bundesregierung*. No feature is added
+/// when the lemma is not a compound.
+pub struct AddCompoundSplit {
+    splitter: CompoundSplitter,
+}
+
+impl AddCompoundSplit {
+    pub fn new(splitter: CompoundSplitter) -> Self {
+        AddCompoundSplit { splitter }
+    }
+}
+
+impl MiscTransform for AddCompoundSplit {
+    fn key(&self) -> &str {
+        COMPOUND_FEATURE
+    }
+
+    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> Option<String> {
+        let token = graph.token(node);
+
+        if token.xpos() != NOUN_TAG {
+            return None;
+        }
+
+        self.splitter
+            .split(token.lemma())
+            .map(|compound| compound.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    use fst::Set;
+
+    use crate::transform::test_helpers::run_misc_test_cases;
+
+    use super::{AddCompoundSplit, CompoundSplitter};
+
+    fn test_splitter() -> CompoundSplitter {
+        let f = File::open("testdata/compound-lexicon.txt").unwrap();
+        CompoundSplitter::read_lexicon(BufReader::new(f)).unwrap()
+    }
+
+    #[test]
+    pub fn add_compound_split() {
+        run_misc_test_cases(
+            "testdata/add-compound-split.test",
+            AddCompoundSplit::new(test_splitter()),
+        );
+    }
+
+    #[test]
+    pub fn compound_has_head() {
+        let splitter = test_splitter();
+        let compound = splitter.split("Haustürschlüssel").unwrap();
+        assert_eq!(compound.head(), "schlüssel");
+        assert_eq!(compound.constituents().len(), 3);
+        assert_eq!(compound.constituents()[0].stem(), "haus");
+        assert_eq!(compound.constituents()[0].linking_element(), None);
+
+        let compound = splitter.split("Bundesregierung").unwrap();
+        assert_eq!(compound.head(), "regierung");
+        assert_eq!(compound.constituents()[0].linking_element(), Some("es"));
+    }
+
+    #[test]
+    pub fn splitter_from_set() {
+        let lexicon = Set::from_iter(vec!["bahn", "straße"]).unwrap();
+        let splitter = CompoundSplitter::new(lexicon).unwrap();
+        assert_eq!(
+            splitter.split("Straßenbahn").unwrap().to_string(),
+            "straße(n)+bahn"
+        );
+
+        let invalid = Set::from_iter(vec![&b"bahn"[..], &b"stra\xdfe"[..]]).unwrap();
+        assert!(CompoundSplitter::new(invalid).is_err());
+    }
+
+    #[test]
+    pub fn long_words_are_split_efficiently() {
+        // Every split point of the word is ambiguous, so there are
+        // exponentially many segmentations.
+        let splitter =
+            CompoundSplitter::read_lexicon(Cursor::new("aaa\naaaa\naaaaa\naaaaaa\n")).unwrap();
+        let compound = splitter.split(&"a".repeat(120)).unwrap();
+        assert_eq!(compound.constituents().len(), 20);
+        assert_eq!(compound.head(), "aaaaaa");
+    }
+}
@@ -3,8 +3,10 @@
 //! This module provides transformations that converts TüBa-D/Z-style lemmas
 //! to `regular' lemmas.
 
+use std::borrow::Cow;
+
 use crate::constants::*;
-use crate::transform::{DependencyGraph, Transform};
+use crate::transform::{DependencyGraph, LemmaScope, Transform};
 
 /// Remove alternative lemma analyses.
 ///
@@ -13,7 +15,11 @@ use crate::transform::{DependencyGraph, Transform};
 pub struct RemoveAlternatives;
 
 impl Transform for RemoveAlternatives {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let mut lemma = token.lemma();
 
@@ -21,33 +27,42 @@ impl Transform for RemoveAlternatives {
             || token.xpos() == NON_WORD_TAG
             || token.xpos() == FOREIGN_WORD_TAG
         {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         if let Some(idx) = lemma.find('|') {
             lemma = &lemma[..idx];
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
 /// Replace reflexive tag.
 ///
 /// Reflexives use the special *#refl* lemma in TüBa-D/Z. This transformation
-/// replaces this pseudo-lemma by the lowercased form.
+/// replaces this pseudo-lemma by the lowercased form. Besides reflexive
+/// personal pronouns (PRF), the lemma is also replaced for tokens with other
+/// tags that have the *#refl* lemma, such as personal pronouns that were
+/// marked by `AddContextualReflexiveTag`.
 pub struct RemoveReflexiveTag;
 
 impl Transform for RemoveReflexiveTag {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
 
-        if token.xpos() == REFLEXIVE_PERSONAL_PRONOUN_TAG {
-            return token.form().to_lowercase();
+        if token.xpos() == REFLEXIVE_PERSONAL_PRONOUN_TAG
+            || lemma == REFLEXIVE_PERSONAL_PRONOUN_LEMMA
+        {
+            return Cow::Owned(token.form().to_lowercase());
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
@@ -60,7 +75,11 @@ impl Transform for RemoveReflexiveTag {
 pub struct RemoveSepVerbPrefix;
 
 impl Transform for RemoveSepVerbPrefix {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let mut lemma = token.lemma();
 
@@ -70,7 +89,75 @@ impl Transform for RemoveSepVerbPrefix {
             }
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
+    }
+}
+
+/// Remove separated prefixes from verbs.
+///
+/// In contrast to `RemoveSepVerbPrefix`, this transformation only removes
+/// prefixes that are not attached to the form, so that the lemma stays
+/// consistent with the form. For example, *ab#fangen* is transformed to
+/// *abfangen* for the form *abgefangen*, but to *fangen* for the form
+/// *fängt* in *fängt ... ab*. Prefixes that are separated particles
+/// (*PTKVZ*) of the verb are always removed, while the attached prefixes
+/// that follow them are retained. For example, *wieder#auf#bauen* is
+/// transformed to *aufbauen* for *aufgebaut* with the particle *wieder*.
+pub struct RemoveSeparatedVerbPrefix;
+
+impl Transform for RemoveSeparatedVerbPrefix {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+        let token = graph.token(node);
+        let lemma = token.lemma();
+
+        if !is_verb(token.xpos()) {
+            return Cow::Borrowed(lemma);
+        }
+
+        let (prefixes, verb) = match lemma.rfind('#') {
+            Some(idx) => (&lemma[..idx], &lemma[idx + 1..]),
+            None => return Cow::Borrowed(lemma),
+        };
+
+        let particles = graph
+            .dependents(node)
+            .map(|(dependent, _)| graph.token(dependent))
+            .filter(|dependent| dependent.xpos() == SEPARABLE_PARTICLE_POS)
+            .map(|dependent| dependent.form().to_lowercase())
+            .collect::<Vec<_>>();
+
+        // Retain the prefixes that the form starts with, skipping prefixes
+        // that are separated particles and stopping at the first prefix
+        // that is not attached.
+        let form = token.form().to_lowercase();
+        let mut unmatched = form.as_str();
+        let mut attached = String::new();
+        for prefix in prefixes.split('#') {
+            if particles
+                .iter()
+                .any(|particle| particle.starts_with(prefix))
+            {
+                continue;
+            }
+
+            unmatched = match unmatched.strip_prefix(prefix) {
+                Some(unmatched) => unmatched,
+                None => break,
+            };
+
+            attached.push_str(prefix);
+        }
+
+        if attached.is_empty() {
+            Cow::Borrowed(verb)
+        } else {
+            attached.push_str(verb);
+            Cow::Owned(attached)
+        }
     }
 }
 
@@ -90,18 +177,26 @@ impl Transform for RemoveSepVerbPrefix {
 pub struct RemoveTruncMarker;
 
 impl Transform for RemoveTruncMarker {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![TRUNCATED_TAG])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
 
         if token.xpos() != TRUNCATED_TAG {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         if token.upos() == "NOUN" {
-            token.form().to_owned()
+            Cow::Borrowed(token.form())
         } else {
-            token.form().to_lowercase()
+            Cow::Owned(token.form().to_lowercase())
         }
     }
 }
@@ -110,13 +205,28 @@ impl Transform for RemoveTruncMarker {
 mod tests {
     use crate::transform::test_helpers::run_test_cases;
 
-    use super::{RemoveSepVerbPrefix, RemoveTruncMarker};
+    use super::{
+        RemoveReflexiveTag, RemoveSepVerbPrefix, RemoveSeparatedVerbPrefix, RemoveTruncMarker,
+    };
+
+    #[test]
+    pub fn remove_reflexive_tag() {
+        run_test_cases("testdata/remove-reflexive-tag.test", RemoveReflexiveTag);
+    }
 
     #[test]
     pub fn remove_sep_verb_prefix() {
         run_test_cases("testdata/remove-sep-verb-prefix.test", RemoveSepVerbPrefix);
     }
 
+    #[test]
+    pub fn remove_separated_verb_prefix() {
+        run_test_cases(
+            "testdata/remove-separated-verb-prefix.test",
+            RemoveSeparatedVerbPrefix,
+        );
+    }
+
     #[test]
     pub fn remove_trunc_marker() {
         run_test_cases("testdata/remove-trunc-marker.test", RemoveTruncMarker);
@@ -3,33 +3,215 @@
 //! This module provides transformations that converts lemmas to TüBa-D/Z-style
 //! lemmas.
 
-use std::collections::HashMap;
-use std::io::{BufRead, Cursor};
+use std::borrow::Cow;
 
-use fst::{Set, SetBuilder};
+use fst::Set;
 
 use crate::constants::*;
+use crate::data::DataBundle;
+use crate::transform::lexicon::SeparableVerbs;
 use crate::transform::named_entity::restore_named_entity_case;
-use crate::transform::svp::longest_prefixes;
-use crate::transform::{DependencyGraph, Transform};
-use crate::LemmatizationError;
+use crate::transform::svp::{lemma_prefixes, longest_prefixes};
+use crate::transform::{DependencyGraph, LemmaScope, TokenSequence, Transform};
 
 /// Set the lemma of reflexive personal pronouns (PRF) to `#refl`.
 pub struct AddReflexiveTag;
 
 impl Transform for AddReflexiveTag {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![REFLEXIVE_PERSONAL_PRONOUN_TAG])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
 
         if token.xpos() == REFLEXIVE_PERSONAL_PRONOUN_TAG {
-            REFLEXIVE_PERSONAL_PRONOUN_LEMMA.to_owned()
+            Cow::Borrowed(REFLEXIVE_PERSONAL_PRONOUN_LEMMA)
+        } else {
+            Cow::Borrowed(lemma)
+        }
+    }
+}
+
+/// Confidence that is required to mark a personal pronoun as reflexive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReflexiveConfidence {
+    /// The governing verb must be an inherently reflexive verb, or an
+    /// optionally reflexive verb with a subject that agrees in person
+    /// with the pronoun.
+    High,
+
+    /// The governing verb must be a reflexive verb or have a subject
+    /// that agrees in person with the pronoun.
+    Low,
+}
+
+/// Set the lemma of personal pronouns (PPER) that are used reflexively
+/// to `#refl`.
+///
+/// Taggers frequently tag reflexive uses of *mich*, *mir*, *dich*, *dir*,
+/// *uns*, and *euch* as personal pronouns. This transformation uses the
+/// dependency structure to find such reflexives. A pronoun is only
+/// considered to be reflexive when it is attached to its governing verb
+/// as an accusative or dative object. Then, depending on the confidence:
+///
+/// * `High`: the lemma of the governing verb must be in the list of
+///   inherently reflexive verbs (e.g. *Ich beeile mich*), or in the list
+///   of optionally reflexive verbs with a subject that has the same person
+///   as the pronoun (e.g. *Ich freue mich*, but not *Das freut mich*).
+/// * `Low`: the lemma of the governing verb must be in one of the lists of
+///   reflexive verbs, or the subject of the verb must have the same
+///   person as the pronoun (e.g. *Ich sehe mich*).
+///
+/// In both cases, the pronoun is not marked as reflexive when the verb has
+/// a subject with a different person (e.g. *Er erinnert mich*). The subject
+/// of an auxiliary verb is used when the governing verb is attached to an
+/// auxiliary. Imperatives without a subject have an implied second person
+/// subject, so *Frag mich* is not reflexive.
+pub struct AddContextualReflexiveTag {
+    confidence: ReflexiveConfidence,
+    reflexive_verbs: Set<Cow<'static, [u8]>>,
+    optionally_reflexive_verbs: Set<Cow<'static, [u8]>>,
+}
+
+impl AddContextualReflexiveTag {
+    /// Create this transformation using the bundled lists of reflexive
+    /// verbs.
+    pub fn new(confidence: ReflexiveConfidence) -> Self {
+        Self::with_data(confidence, &DataBundle::default())
+    }
+
+    /// Create this transformation using the lists of reflexive verbs of
+    /// the given data bundle.
+    pub fn with_data(confidence: ReflexiveConfidence, data: &DataBundle) -> Self {
+        AddContextualReflexiveTag {
+            confidence,
+            reflexive_verbs: data.reflexive_verbs(),
+            optionally_reflexive_verbs: data.optionally_reflexive_verbs(),
+        }
+    }
+
+    /// Replace the list of inherently reflexive verbs.
+    ///
+    /// These are verbs whose pronoun objects are reflexive, regardless of
+    /// the subject, e.g. *beeilen*. Verbs should be lowercased infinitives
+    /// without separable prefix markers, e.g. *ausruhen*.
+    pub fn set_reflexive_verbs(&mut self, reflexive_verbs: Set<Cow<'static, [u8]>>) {
+        self.reflexive_verbs = reflexive_verbs;
+    }
+
+    /// Replace the list of optionally reflexive verbs.
+    ///
+    /// These are verbs that have both reflexive and non-reflexive
+    /// readings, e.g. *vorstellen*.
+    pub fn set_optionally_reflexive_verbs(
+        &mut self,
+        optionally_reflexive_verbs: Set<Cow<'static, [u8]>>,
+    ) {
+        self.optionally_reflexive_verbs = optionally_reflexive_verbs;
+    }
+
+    /// Check whether the subject of a verb agrees with the pronoun.
+    ///
+    /// The implied subject of an imperative is in the second person.
+    /// Returns `None` if the verb does not have a subject.
+    fn subject_agrees(
+        &self,
+        graph: &dyn DependencyGraph,
+        verb: usize,
+        pronoun_subject: &str,
+    ) -> Option<bool> {
+        let subject = graph
+            .dependents(verb)
+            .find(|(_, rel)| *rel == SUBJECT_RELATION)
+            .map(|(dependent, _)| dependent);
+
+        match subject {
+            Some(subject) => Some(graph.token(subject).form().to_lowercase() == pronoun_subject),
+            None if graph.token(verb).xpos() == IMPERATIVE_VERB_TAG => {
+                Some(SECOND_PERSON_SUBJECTS.contains(&pronoun_subject))
+            }
+            None => match graph.head(verb) {
+                Some((head, rel)) if rel == AUXILIARY_RELATION => {
+                    self.subject_agrees(graph, head, pronoun_subject)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Transform for AddContextualReflexiveTag {
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![PERSONAL_PRONOUN_TAG])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+        let token = graph.token(node);
+        let lemma = token.lemma();
+
+        if token.xpos() != PERSONAL_PRONOUN_TAG {
+            return Cow::Borrowed(lemma);
+        }
+
+        let form = token.form().to_lowercase();
+        let pronoun_subject = ok_or!(
+            REFLEXIVE_PERSONAL_PRONOUN_SUBJECTS.get(form.as_str()),
+            return Cow::Borrowed(lemma)
+        );
+
+        let (verb, rel) = ok_or!(graph.head(node), return Cow::Borrowed(lemma));
+        if (rel != ACCUSATIVE_OBJECT_RELATION && rel != DATIVE_OBJECT_RELATION)
+            || !is_verb(graph.token(verb).xpos())
+        {
+            return Cow::Borrowed(lemma);
+        }
+
+        let verb_key = reflexive_verb_key(graph.token(verb).lemma());
+        let is_reflexive_verb = self.reflexive_verbs.contains(&verb_key);
+        let is_optionally_reflexive_verb = self.optionally_reflexive_verbs.contains(&verb_key);
+
+        let is_reflexive = match (
+            self.confidence,
+            self.subject_agrees(graph, verb, pronoun_subject),
+        ) {
+            (_, Some(false)) => false,
+            (ReflexiveConfidence::High, Some(true)) => {
+                is_reflexive_verb || is_optionally_reflexive_verb
+            }
+            (ReflexiveConfidence::High, None) => is_reflexive_verb,
+            (ReflexiveConfidence::Low, agrees) => {
+                is_reflexive_verb || is_optionally_reflexive_verb || agrees == Some(true)
+            }
+        };
+
+        if is_reflexive {
+            Cow::Borrowed(REFLEXIVE_PERSONAL_PRONOUN_LEMMA)
         } else {
-            lemma.to_owned()
+            Cow::Borrowed(lemma)
         }
     }
 }
 
+/// Normalize a verb lemma for lookups in the reflexive verb list.
+///
+/// Only the first alternative is used, separable prefix markers are removed,
+/// and the *%aux*/*%passiv* tags are stripped.
+fn reflexive_verb_key(lemma: &str) -> String {
+    let mut lemma = lemma.split('|').next().unwrap_or(lemma);
+
+    if let Some(idx) = lemma.find('%') {
+        lemma = &lemma[..idx];
+    }
+
+    lemma.replace('#', "").to_lowercase()
+}
+
 /// Add separable verb prefixes to verbs.
 ///
 /// TüBa-D/Z marks separable verb prefixes in the verb lemma. E.g. *ab#zeichnen*,
@@ -57,16 +239,22 @@ impl AddSeparatedVerbPrefix {
 }
 
 impl Transform for AddSeparatedVerbPrefix {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(SEPARABLE_VERB_TAGS.to_vec())
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
 
         if !is_separable_verb(token.xpos()) {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
-        let mut lemma = lemma.to_owned();
-
         // Find all nodes that are attached with the separable verb dependency
         // relation.
         //
@@ -86,17 +274,84 @@ impl Transform for AddSeparatedVerbPrefix {
             }
 
             if lemmas.is_empty() {
-                lemma
+                Cow::Borrowed(lemma)
             } else {
-                lemmas.join("|")
+                Cow::Owned(lemmas.join("|"))
             }
+        } else if let Some((dependant, _)) = prefix_iter.next() {
+            let prefix = graph.token(dependant);
+            Cow::Owned(format!("{}#{}", prefix.form().to_lowercase(), lemma))
         } else {
-            if let Some((dependant, _)) = prefix_iter.next() {
-                let prefix = graph.token(dependant);
-                lemma.insert_str(0, &format!("{}#", prefix.form().to_lowercase()));
-            }
+            Cow::Borrowed(lemma)
+        }
+    }
+}
+
+/// Add separable verb prefixes to verbs, without a dependency parse.
+///
+/// This transformation is an alternative to `AddSeparatedVerbPrefix` for
+/// input that is only tagged. Each separable particle (*PTKVZ*) is
+/// attached to the closest preceding finite verb in the same clause. The
+/// clause ends at a comma, sentence-final punctuation, or a subordinating
+/// conjunction (*KOUS*). For example, *zeichnen* in
+///
+/// *Diese Änderungen zeichnen sich bereits ab .*
+///
+/// is lemmatized as *ab#zeichnen*. With multiple prefixes, the prefixes
+/// are used in sentence order, e.g. *ab#nehmen|zu#nehmen* for
+/// *nimmt eher ab als zu*.
+pub struct AddSeparatedVerbPrefixLinear {
+    multiple_prefixes: bool,
+}
+
+impl AddSeparatedVerbPrefixLinear {
+    pub fn new(multiple_prefixes: bool) -> Self {
+        AddSeparatedVerbPrefixLinear { multiple_prefixes }
+    }
+}
+
+impl Transform for AddSeparatedVerbPrefixLinear {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(FINITE_VERB_TAGS.to_vec())
+    }
 
-            lemma
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+        let token = graph.token(node);
+        let lemma = token.lemma();
+
+        if !FINITE_VERB_TAGS.contains(&token.xpos()) {
+            return Cow::Borrowed(lemma);
+        }
+
+        // Particles that follow the verb, up to the end of the clause or
+        // the next finite verb.
+        let mut prefix_iter = TokenSequence::new(graph)
+            .following(node)
+            .map(|(_, token)| token)
+            .take_while(|token| {
+                !CLAUSE_BOUNDARY_TAGS.contains(&token.xpos())
+                    && !FINITE_VERB_TAGS.contains(&token.xpos())
+            })
+            .filter(|token| token.xpos() == SEPARABLE_PARTICLE_POS);
+
+        if self.multiple_prefixes {
+            let lemmas = prefix_iter
+                .map(|prefix| format!("{}#{}", prefix.form().to_lowercase(), lemma))
+                .collect::<Vec<_>>();
+
+            if lemmas.is_empty() {
+                Cow::Borrowed(lemma)
+            } else {
+                Cow::Owned(lemmas.join("|"))
+            }
+        } else if let Some(prefix) = prefix_iter.next() {
+            Cow::Owned(format!("{}#{}", prefix.form().to_lowercase(), lemma))
+        } else {
+            Cow::Borrowed(lemma)
         }
     }
 }
@@ -105,16 +360,30 @@ impl Transform for AddSeparatedVerbPrefix {
 pub struct FormAsLemma;
 
 impl Transform for FormAsLemma {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(
+            LEMMA_IS_FORM_TAGS
+                .iter()
+                .chain(LEMMA_IS_FORM_PRESERVE_CASE_TAGS.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
 
         // Handle tags for which the lemma is the lowercased form.
         if LEMMA_IS_FORM_TAGS.contains(token.xpos()) {
-            token.form().to_lowercase()
+            Cow::Owned(token.form().to_lowercase())
         } else if LEMMA_IS_FORM_PRESERVE_CASE_TAGS.contains(token.xpos()) {
-            token.form().to_owned()
+            Cow::Borrowed(token.form())
         } else {
-            token.lemma().to_owned()
+            Cow::Borrowed(token.lemma())
         }
     }
 }
@@ -139,25 +408,175 @@ impl Transform for FormAsLemma {
 ///
 /// In 'zu'-infinitives *zu* is removed and not analyzed as being (part of) a
 /// separable prefix.
+///
+/// Some prefixes, such as *über* and *um*, are separable in some verbs and
+/// inseparable in others. Participles and 'zu'-infinitives of separable
+/// verbs have the infix *ge* or *zu* after the prefix, so *übergesetzt* is
+/// analyzed as *über#setzen*, but *übersetzt* as *übersetzen*. Other forms
+/// are only analyzed as inseparable when the verb is in the lexicon of
+/// inseparable verbs.
+///
+/// When the lemmatizer did not strip the prefixes and the lemma is not in
+/// the lookup table of prefix verbs, prefixes are removed from the lemma
+/// itself, e.g. *ausgehen* -> *aus#gehen*. Since many verbs start with a
+/// string that is also a prefix, the remainder of the lemma must be in the
+/// lexicon of base verbs. So, *herrschen* is not analyzed as *her#rschen*.
 pub struct MarkVerbPrefix {
-    prefix_verbs: HashMap<String, String>,
-    prefixes: Set<Vec<u8>>,
+    prefix_verbs: SeparableVerbs,
+    base_verbs: Set<Cow<'static, [u8]>>,
+    inseparable_verbs: Set<Cow<'static, [u8]>>,
+    prefixes: Set<Cow<'static, [u8]>>,
 }
 
 impl MarkVerbPrefix {
     /// Create this transformation. A simple lookup for prefix verbs can be
     /// provided. More crucially, a set of prefixes must be provided to find
     /// prefixes.
+    ///
+    /// The bundled TüBa-D/Z list of separable verbs is used for lookups
+    /// and as the lexicon of base verbs.
     pub fn new() -> Self {
-        MarkVerbPrefix::read_verb_prefixes(Cursor::new(include_str!(
-            "../../data/tdz11-separable-prefixes.txt"
-        )))
-        .expect("Invalid separable verb prefix data")
+        Self::with_data(&DataBundle::default())
+    }
+
+    /// Create this transformation using the lexicons of the given data
+    /// bundle.
+    pub fn with_data(data: &DataBundle) -> Self {
+        MarkVerbPrefix {
+            prefix_verbs: data.separable_verbs(),
+            base_verbs: data.separable_base_verbs(),
+            inseparable_verbs: data.inseparable_verbs(),
+            prefixes: data.separable_prefixes(),
+        }
+    }
+
+    /// Replace the lexicon of inseparable verbs.
+    ///
+    /// Verbs should be lowercased infinitives, e.g. *übersetzen*. The
+    /// lexicon is not used for participles and 'zu'-infinitives, since
+    /// their separability follows from the form.
+    pub fn set_inseparable_verbs(&mut self, inseparable_verbs: Set<Cow<'static, [u8]>>) {
+        self.inseparable_verbs = inseparable_verbs;
+    }
+
+    /// Replace the lexicon of base verbs.
+    ///
+    /// Prefixes are only removed from a lemma when the remainder is in
+    /// this lexicon. Verbs should be lowercased infinitives without
+    /// prefixes, e.g. *gehen*.
+    pub fn set_base_verbs(&mut self, base_verbs: Set<Cow<'static, [u8]>>) {
+        self.base_verbs = base_verbs;
+    }
+
+    /// Replace the lookup table of prefix verbs.
+    ///
+    /// The table maps lowercased verbs to lemmas with marked prefixes,
+    /// e.g. *abzeichnen* to *ab#zeichnen*. A `HashMap` from
+    /// `lexicon::read_separable_verbs` can be used as well.
+    pub fn set_prefix_verbs<V>(&mut self, prefix_verbs: V)
+    where
+        V: Into<SeparableVerbs>,
+    {
+        self.prefix_verbs = prefix_verbs.into();
+    }
+
+    /// Replace the set of separable verb prefixes.
+    ///
+    /// Prefixes should be lowercased, e.g. *ab*.
+    pub fn set_prefixes(&mut self, prefixes: Set<Cow<'static, [u8]>>) {
+        self.prefixes = prefixes;
     }
 
-    pub fn set_prefix_verbs(&mut self, prefix_verbs: HashMap<String, String>) {
-        self.prefix_verbs = prefix_verbs;
+    /// Check whether the last prefix of a verb is inseparable.
+    ///
+    /// `stripped_form` is the form without prefixes, `lemma` the lemma
+    /// without prefixes.
+    fn is_inseparable(&self, prefix: &str, stripped_form: &str, lemma: &str, tag: &str) -> bool {
+        if !AMBIGUOUS_VERB_PREFIXES.contains(&prefix) {
+            return false;
+        }
+
+        if let Some(inseparable) = form_is_inseparable(stripped_form, lemma, tag) {
+            return inseparable;
+        }
+
+        let mut verb = String::with_capacity(prefix.len() + lemma.len());
+        verb.push_str(prefix);
+        verb.push_str(lemma);
+        self.inseparable_verbs.contains(&verb)
+    }
+}
+
+/// Check whether the form shows that the last prefix of a verb is
+/// inseparable.
+///
+/// Participles and 'zu'-infinitives of separable verbs have the infix
+/// *ge* or *zu* after the prefix. `stripped_form` is the form without
+/// prefixes, `lemma` the lemma without prefixes. Returns `None` when the
+/// form is not evidence either way.
+fn form_is_inseparable(stripped_form: &str, lemma: &str, tag: &str) -> Option<bool> {
+    if tag == ZU_INFINITIVE_VERB {
+        return Some(!stripped_form.starts_with("zu"));
+    }
+
+    if PARTICIPLE_TAGS.contains(&tag) && has_ge_participle(lemma) {
+        // The participle stem starts with the same consonants as the
+        // lemma, even when the stem vowel changes (*gehen*, *gegangen*).
+        let onset = consonant_onset(lemma);
+
+        if let Some(stem) = stripped_form.strip_prefix("ge") {
+            if consonant_onset(stem) == onset {
+                return Some(false);
+            }
+        }
+
+        // Without the infix, the participle stem should directly follow
+        // the prefix. Otherwise, the form may contain parts that are not
+        // in the prefix set.
+        if consonant_onset(stripped_form) == onset {
+            return Some(true);
+        }
     }
+
+    None
+}
+
+/// Check whether the participle of a verb is formed with *ge-*.
+fn has_ge_participle(lemma: &str) -> bool {
+    !(UNSTRESSED_VERB_PREFIXES
+        .iter()
+        .any(|prefix| lemma.starts_with(prefix))
+        || has_unstressed_ge_prefix(lemma)
+        || lemma.ends_with("ieren"))
+}
+
+/// Check whether a verb starts with the unstressed prefix *ge-*.
+///
+/// In verbs such as *geben* and *gehen*, *ge* is part of the stem. The
+/// prefix is only split off when the remaining stem starts with a consonant
+/// and still has a vowel (*gewinnen*, *gehören*).
+fn has_unstressed_ge_prefix(lemma: &str) -> bool {
+    let stem = match lemma.strip_prefix("ge") {
+        Some(rest) if !rest.starts_with(is_vowel) => rest,
+        _ => return false,
+    };
+
+    let stem = stem
+        .strip_suffix("en")
+        .or_else(|| stem.strip_suffix('n'))
+        .unwrap_or(stem);
+
+    stem.chars().any(is_vowel)
+}
+
+/// Get the consonants that precede the first vowel of a word.
+fn consonant_onset(word: &str) -> &str {
+    let end = word.find(is_vowel).unwrap_or(word.len());
+    &word[..end]
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiouyäöü".contains(c)
 }
 
 impl Default for MarkVerbPrefix {
@@ -167,83 +586,235 @@ impl Default for MarkVerbPrefix {
 }
 
 impl Transform for MarkVerbPrefix {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
-        let lemma_lc = lemma.to_lowercase();
 
         if !is_verb(token.xpos()) {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
+        let lemma_lc = lemma.to_lowercase();
+
         // There are two cases that we have to handle separately:
         //
         // 1. The lemmatizer did not strip the prefix. In this case, we
-        //    perform a lemma lookup. For now, removing prefixes from the
-        //    lemma itself seems to be too tricky.
+        //    perform a lemma lookup. If the lemma is not in the lookup
+        //    table, we remove prefixes from the lemma itself.
         //
         // 2. The lemmatizer stripped the prefix. The prefix needs to be
         //    inferred from the token's form.
 
         // Case 1: try a simple lookup for the lemma
         if let Some(sep_lemma) = self.prefix_verbs.get(&lemma_lc) {
-            return sep_lemma.clone();
+            return Cow::Owned(sep_lemma);
+        }
+
+        // Otherwise, find prefixes in the lemma, such that the remainder is
+        // a base verb.
+        let mut lemma_parts = lemma_prefixes(&self.prefixes, &lemma_lc, |verb| {
+            self.base_verbs.contains(verb)
+        });
+        if let Some(prefix) = lemma_parts.last() {
+            let mut verb_start = lemma_parts.iter().map(String::len).sum::<usize>();
+
+            // The lemma has no infixes, so an ambiguous last prefix is
+            // disambiguated by the form when it contains the prefixes.
+            // Otherwise, the prefix is only separable when the separable
+            // lexicon lists the verb.
+            let inseparable_start = verb_start - prefix.len();
+            if AMBIGUOUS_VERB_PREFIXES.contains(&prefix.as_str()) {
+                let form_lc = token.form().to_lowercase();
+                let inseparable = form_lc
+                    .strip_prefix(&lemma_lc[..verb_start])
+                    .and_then(|stripped_form| {
+                        form_is_inseparable(stripped_form, &lemma_lc[verb_start..], token.xpos())
+                    })
+                    .unwrap_or_else(|| !self.prefix_verbs.contains(&lemma_lc[inseparable_start..]));
+
+                if inseparable {
+                    lemma_parts.pop();
+                    verb_start = inseparable_start;
+                }
+            }
+
+            if lemma_parts.is_empty() {
+                return Cow::Borrowed(lemma);
+            }
+
+            lemma_parts.push(lemma_lc[verb_start..].to_owned());
+            return Cow::Owned(lemma_parts.join("#"));
         }
 
         // Case 2: there are no prefixes in the lemma, try to find prefixes
         // in the form.
         let form_lc = token.form().to_lowercase();
         let mut lemma_parts = longest_prefixes(&self.prefixes, &form_lc, &lemma_lc, token.xpos());
-        if !lemma_parts.is_empty() {
-            lemma_parts.push(lemma_lc);
-            return lemma_parts.join("#");
+        if let Some(prefix) = lemma_parts.last() {
+            let prefixes_len = lemma_parts.iter().map(String::len).sum::<usize>();
+            let mut verb = lemma_lc;
+
+            // An inseparable prefix is part of the verb.
+            if self.is_inseparable(prefix, &form_lc[prefixes_len..], &verb, token.xpos()) {
+                verb.insert_str(0, prefix);
+                lemma_parts.pop();
+            }
+
+            lemma_parts.push(verb);
+            return Cow::Owned(lemma_parts.join("#"));
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
-trait ReadVerbPrefixes {
-    fn read_verb_prefixes<R>(r: R) -> Result<MarkVerbPrefix, LemmatizationError>
-    where
-        R: BufRead;
+/// Add separable verb prefixes to verbs, combining separated and attached
+/// prefixes.
+///
+/// This transformation unifies `AddSeparatedVerbPrefix` and
+/// `MarkVerbPrefix`. Prefixes that are attached to the form are found
+/// with `MarkVerbPrefix`, separated particles through the dependency
+/// structure. Separated particles precede the attached prefixes in the
+/// lemma, so that *anfing* in
+///
+/// *[...] als sie wieder anfing*
+///
+/// is lemmatized as *wieder#an#fangen*. Multiple separated particles are
+/// combined in sentence order (*fing ... wieder an* -> *wieder#an#fangen*),
+/// unless they are coordinated (*KON*). Coordinated particles are
+/// alternatives, e.g. *ab#nehmen|zu#nehmen* for *nimmt eher ab als zu*.
+pub struct AddSeparableVerbPrefixes {
+    attached: MarkVerbPrefix,
+    multiple_prefixes: bool,
 }
 
-impl ReadVerbPrefixes for MarkVerbPrefix {
-    fn read_verb_prefixes<R>(r: R) -> Result<MarkVerbPrefix, LemmatizationError>
-    where
-        R: BufRead,
-    {
-        let mut builder = SetBuilder::memory();
+impl AddSeparableVerbPrefixes {
+    /// Create this transformation, using `MarkVerbPrefix` with the bundled
+    /// data for attached prefixes.
+    pub fn new(multiple_prefixes: bool) -> Self {
+        Self::with_mark_verb_prefix(MarkVerbPrefix::new(), multiple_prefixes)
+    }
+
+    /// Create this transformation, using the given `MarkVerbPrefix` for
+    /// attached prefixes.
+    pub fn with_mark_verb_prefix(attached: MarkVerbPrefix, multiple_prefixes: bool) -> Self {
+        AddSeparableVerbPrefixes {
+            attached,
+            multiple_prefixes,
+        }
+    }
+}
+
+impl Transform for AddSeparableVerbPrefixes {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
 
-        for line in r.lines() {
-            let line = line?;
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+        let token = graph.token(node);
 
-            builder.insert(&line)?;
+        if !is_verb(token.xpos()) {
+            return Cow::Borrowed(token.lemma());
         }
 
-        let bytes = builder.into_inner()?;
-        let prefixes = Set::new(bytes)?;
+        let verb = self.attached.transform(graph, node);
+
+        if !is_separable_verb(token.xpos()) {
+            return verb;
+        }
+
+        let mut alternatives = separated_prefixes(graph, node);
+        if alternatives.is_empty() {
+            return verb;
+        }
+
+        if !self.multiple_prefixes {
+            alternatives.truncate(1);
+        }
+
+        Cow::Owned(
+            alternatives
+                .iter()
+                .map(|prefixes| format!("{}#{}", prefixes, verb))
+                .collect::<Vec<_>>()
+                .join("|"),
+        )
+    }
+}
+
+/// Get the alternative separated prefixes of a verb.
+///
+/// Particles that are not coordinated are combined in sentence order,
+/// e.g. *wieder#an*. Each coordinated particle is an alternative.
+fn separated_prefixes(graph: &dyn DependencyGraph, node: usize) -> Vec<String> {
+    let is_particle = |idx: usize| graph.token(idx).xpos() == SEPARABLE_PARTICLE_POS;
+
+    // Particles with a flag that indicates whether they are coordinated.
+    let mut particles = Vec::new();
+    for (dependent, relation) in graph.dependents(node) {
+        if !is_particle(dependent) {
+            continue;
+        }
+
+        particles.push((dependent, relation == COORDINATION_RELATION));
+        particles.extend(
+            graph
+                .dependents(dependent)
+                .filter(|&(conjunct, relation)| {
+                    relation == COORDINATION_RELATION && is_particle(conjunct)
+                })
+                .map(|(conjunct, _)| (conjunct, true)),
+        );
+    }
+
+    particles.sort_unstable();
+
+    let prefix = |&(idx, _): &(usize, bool)| graph.token(idx).form().to_lowercase();
+
+    let combined = particles
+        .iter()
+        .filter(|(_, coordinated)| !coordinated)
+        .map(prefix)
+        .collect::<Vec<_>>();
 
-        Ok(MarkVerbPrefix {
-            prefix_verbs: HashMap::new(),
-            prefixes,
-        })
+    let mut alternatives = Vec::new();
+    if !combined.is_empty() {
+        alternatives.push(combined.join("#"));
     }
+    alternatives.extend(
+        particles
+            .iter()
+            .filter(|(_, coordinated)| *coordinated)
+            .map(prefix),
+    );
+
+    alternatives
 }
 
 pub struct RestoreCase;
 
 impl Transform for RestoreCase {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![NOUN_TAG, NAMED_ENTITY_TAG])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
 
         if token.xpos() == NOUN_TAG {
-            uppercase_first_char(token.lemma())
+            Cow::Owned(uppercase_first_char(token.lemma()))
         } else if token.xpos() == NAMED_ENTITY_TAG {
-            restore_named_entity_case(token.form(), token.lemma())
+            Cow::Owned(restore_named_entity_case(token.form(), token.lemma()))
         } else {
-            token.lemma().to_owned()
+            Cow::Borrowed(token.lemma())
         }
     }
 }
@@ -267,10 +838,18 @@ mod tests {
     use std::collections::HashMap;
     use std::iter::FromIterator;
 
+    use udgraph::graph::{DepTriple, Sentence};
+    use udgraph::token::TokenBuilder;
+
+    use crate::data::{DataBundle, TuebaRelease};
+    use crate::transform::simple::{SimpleSentence, SimpleToken};
     use crate::transform::test_helpers::run_test_cases;
+    use crate::transform::Transform;
 
     use super::{
-        uppercase_first_char, AddSeparatedVerbPrefix, FormAsLemma, MarkVerbPrefix, RestoreCase,
+        uppercase_first_char, AddContextualReflexiveTag, AddSeparableVerbPrefixes,
+        AddSeparatedVerbPrefix, AddSeparatedVerbPrefixLinear, FormAsLemma, MarkVerbPrefix,
+        ReflexiveConfidence, RestoreCase,
     };
 
     #[test]
@@ -280,6 +859,116 @@ mod tests {
         assert_eq!(uppercase_first_char(""), "");
     }
 
+    #[test]
+    pub fn add_contextual_reflexive_tag() {
+        for &confidence in &[ReflexiveConfidence::High, ReflexiveConfidence::Low] {
+            run_test_cases(
+                "testdata/add-contextual-reflexive-tag.test",
+                AddContextualReflexiveTag::new(confidence),
+            );
+        }
+    }
+
+    #[test]
+    pub fn contextual_reflexive_tag_uses_subject() {
+        let pper = |form| (form, "_", "PPER");
+
+        // Ich habe mich gefreut.
+        let sentence = test_sentence(
+            &[
+                pper("Ich"),
+                ("habe", "haben%aux", "VAFIN"),
+                pper("mich"),
+                ("gefreut", "freuen", "VVPP"),
+            ],
+            &[
+                (0, "ROOT", 2),
+                (2, "SUBJ", 1),
+                (2, "AUX", 4),
+                (4, "OBJA", 3),
+            ],
+        );
+        assert_reflexive(&sentence, 3, true, true);
+
+        // Ich sehe mich.
+        let sentence = test_sentence(
+            &[pper("Ich"), ("sehe", "sehen", "VVFIN"), pper("mich")],
+            &[(0, "ROOT", 2), (2, "SUBJ", 1), (2, "OBJA", 3)],
+        );
+        assert_reflexive(&sentence, 3, false, true);
+
+        // Er erinnert mich.
+        let sentence = test_sentence(
+            &[pper("Er"), ("erinnert", "erinnern", "VVFIN"), pper("mich")],
+            &[(0, "ROOT", 2), (2, "SUBJ", 1), (2, "OBJA", 3)],
+        );
+        assert_reflexive(&sentence, 3, false, false);
+
+        // Das freut mich.
+        let sentence = test_sentence(
+            &[
+                ("Das", "_", "PDS"),
+                ("freut", "freuen", "VVFIN"),
+                pper("mich"),
+            ],
+            &[(0, "ROOT", 2), (2, "SUBJ", 1), (2, "OBJA", 3)],
+        );
+        assert_reflexive(&sentence, 3, false, false);
+    }
+
+    #[test]
+    pub fn contextual_reflexive_tag_optionally_reflexive_without_subject() {
+        // Optionally reflexive verbs require an agreeing subject with high
+        // confidence.
+        for &(pronoun, verb, lemma, xpos) in &[
+            ("mich", "freue", "freuen", "VVFIN"),
+            ("uns", "erinnern", "erinnern", "VVFIN"),
+            ("uns", "trefft", "treffen", "VVFIN"),
+            ("mir", "stelle", "vor#stellen", "VVFIN"),
+            ("mich", "angestrengt", "anstrengen%aux", "VVPP"),
+        ] {
+            let sentence = test_sentence(
+                &[(verb, lemma, xpos), (pronoun, "_", "PPER")],
+                &[(0, "ROOT", 1), (1, "OBJA", 2)],
+            );
+            assert_reflexive(&sentence, 2, false, true);
+        }
+    }
+
+    fn assert_reflexive(sentence: &Sentence, node: usize, high: bool, low: bool) {
+        for &(confidence, reflexive) in &[
+            (ReflexiveConfidence::High, high),
+            (ReflexiveConfidence::Low, low),
+        ] {
+            let lemma = AddContextualReflexiveTag::new(confidence).transform(sentence, node);
+            assert_eq!(lemma == "#refl", reflexive, "confidence: {:?}", confidence);
+        }
+    }
+
+    fn test_sentence(tokens: &[(&str, &str, &str)], edges: &[(usize, &str, usize)]) -> Sentence {
+        let mut sentence = Sentence::new();
+        for &(form, lemma, xpos) in tokens {
+            sentence.push(TokenBuilder::new(form).lemma(lemma).xpos(xpos).into());
+        }
+
+        for &(head, rel, dependent) in edges {
+            sentence
+                .dep_graph_mut()
+                .add_deprel(DepTriple::new(head, Some(rel), dependent))
+                .unwrap();
+        }
+
+        sentence
+    }
+
+    #[test]
+    pub fn add_separable_verb_prefixes() {
+        run_test_cases(
+            "testdata/add-separable-verb-prefixes.test",
+            AddSeparableVerbPrefixes::new(true),
+        );
+    }
+
     #[test]
     pub fn add_separated_verb_prefix() {
         run_test_cases(
@@ -290,6 +979,78 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn add_separated_verb_prefix_linear() {
+        let transform = AddSeparatedVerbPrefixLinear::new(true);
+
+        // Diese Änderungen zeichnen sich bereits ab.
+        let sentence = tagged_sentence(&[
+            ("Diese", "dies", "PDAT"),
+            ("Änderungen", "Änderung", "NN"),
+            ("zeichnen", "zeichnen", "VVFIN"),
+            ("sich", "#refl", "PRF"),
+            ("bereits", "bereits", "ADV"),
+            ("ab", "ab", "PTKVZ"),
+            (".", ".", "$."),
+        ]);
+        assert_eq!(transform.transform(&sentence, 3), "ab#zeichnen");
+        assert_eq!(transform.transform(&sentence, 6), "ab");
+
+        // Er nimmt eher ab als zu.
+        let sentence = tagged_sentence(&[
+            ("Er", "er", "PPER"),
+            ("nimmt", "nehmen", "VVFIN"),
+            ("eher", "eher", "ADV"),
+            ("ab", "ab", "PTKVZ"),
+            ("als", "als", "KOKOM"),
+            ("zu", "zu", "PTKVZ"),
+        ]);
+        assert_eq!(transform.transform(&sentence, 2), "ab#nehmen|zu#nehmen");
+        assert_eq!(
+            AddSeparatedVerbPrefixLinear::new(false).transform(&sentence, 2),
+            "ab#nehmen"
+        );
+    }
+
+    #[test]
+    pub fn add_separated_verb_prefix_linear_respects_clauses() {
+        let transform = AddSeparatedVerbPrefixLinear::new(true);
+
+        // Er sagt, dass sie kommt. Sie hört auf, weil es regnet.
+        let sentence = tagged_sentence(&[
+            ("Er", "er", "PPER"),
+            ("sagt", "sagen", "VVFIN"),
+            (",", ",", "$,"),
+            ("dass", "dass", "KOUS"),
+            ("sie", "sie", "PPER"),
+            ("kommt", "kommen", "VVFIN"),
+            (".", ".", "$."),
+            ("Sie", "sie", "PPER"),
+            ("hört", "hören", "VVFIN"),
+            ("auf", "auf", "PTKVZ"),
+            ("weil", "weil", "KOUS"),
+            ("es", "es", "PPER"),
+            ("regnet", "regnen", "VVFIN"),
+        ]);
+        assert_eq!(transform.transform(&sentence, 2), "sagen");
+        assert_eq!(transform.transform(&sentence, 6), "kommen");
+        assert_eq!(transform.transform(&sentence, 9), "auf#hören");
+        assert_eq!(transform.transform(&sentence, 13), "regnen");
+
+        // Separable prefixes are only added to finite verbs.
+        let sentence =
+            tagged_sentence(&[("anzufangen", "anfangen", "VVIZU"), ("an", "an", "PTKVZ")]);
+        assert_eq!(transform.transform(&sentence, 1), "anfangen");
+    }
+
+    fn tagged_sentence(tokens: &[(&str, &str, &str)]) -> SimpleSentence {
+        let mut sentence = SimpleSentence::new();
+        for &(form, lemma, xpos) in tokens {
+            sentence.push(SimpleToken::new(form).with_lemma(lemma).with_xpos(xpos));
+        }
+        sentence
+    }
+
     #[test]
     pub fn form_as_lemma() {
         run_test_cases("testdata/form-as-lemma.test", FormAsLemma);
@@ -308,6 +1069,14 @@ mod tests {
         run_test_cases("testdata/mark-verb-prefix.test", transform);
     }
 
+    #[test]
+    pub fn mark_verb_prefix_tdz10() {
+        run_test_cases(
+            "testdata/mark-verb-prefix-tdz10.test",
+            MarkVerbPrefix::with_data(&DataBundle::for_release(TuebaRelease::Tdz10)),
+        );
+    }
+
     #[test]
     pub fn restore_case() {
         run_test_cases("testdata/restore-case.test", RestoreCase);
@@ -0,0 +1,179 @@
+//! Separable verb lexicons.
+//!
+//! This module reads lexicons of separable verbs in the notation of the
+//! TüBa-D/Z verb lists, where separable prefixes are marked in the lemma,
+//! e.g. *ab#zeichnen*.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use fst::{IntoStreamer, Set, Streamer};
+
+use crate::transform::lexicon_entry::{compile_entry, expand_line, ENTRY_SEPARATOR};
+use crate::LemmatizationError;
+
+/// A compiled lexicon of separable verbs.
+///
+/// Like the lexicons of `read_separable_verbs`, this lexicon maps
+/// lowercased verbs to lemmas with marked prefixes. The lexicon is stored
+/// as an FST set of *verb\tlemma* entries, so that the bundled lexicon can
+/// be compiled by the build script.
+pub struct SeparableVerbs {
+    entries: Set<Cow<'static, [u8]>>,
+}
+
+impl SeparableVerbs {
+    /// Construct a lexicon from a set of compiled entries.
+    pub(crate) fn from_entries(entries: Set<Cow<'static, [u8]>>) -> Self {
+        SeparableVerbs { entries }
+    }
+
+    /// Get the lemma of a verb.
+    pub fn get(&self, verb: &str) -> Option<String> {
+        let mut start = String::with_capacity(verb.len() + ENTRY_SEPARATOR.len_utf8());
+        start.push_str(verb);
+        start.push(ENTRY_SEPARATOR);
+
+        let mut entries = self.entries.range().ge(&start).into_stream();
+        let lemma = entries.next()?.strip_prefix(start.as_bytes())?;
+        Some(String::from_utf8_lossy(lemma).into_owned())
+    }
+
+    /// Check whether the lexicon contains a verb.
+    pub fn contains(&self, verb: &str) -> bool {
+        self.get(verb).is_some()
+    }
+}
+
+impl From<HashMap<String, String>> for SeparableVerbs {
+    fn from(verbs: HashMap<String, String>) -> Self {
+        let mut entries = verbs
+            .iter()
+            .map(|(verb, lemma)| compile_entry(verb, lemma))
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+
+        let entries = Set::from_iter(entries)
+            .and_then(|entries| entries.map_data(Cow::Owned))
+            .expect("Sorted entries of distinct verbs cannot be out of order");
+
+        SeparableVerbs { entries }
+    }
+}
+
+/// Read a lexicon of separable verbs.
+///
+/// Every non-empty line that does not start with *#* contains one or more
+/// lemmas with marked prefixes, separated by *|*. The lexicon maps each
+/// lemma without markers to the marked lemma, e.g. *abzeichnen* to
+/// *ab#zeichnen*. The following notation is expanded into multiple
+/// entries:
+///
+/// * Optional prefixes are enclosed in parentheses. *(um)#bauen* is
+///   added as *umbauen*, but not as *bauen*, since *bauen* does not have
+///   a separable prefix.
+/// * Prefixes with an elided *her* or *hin* start with an apostrophe.
+///   *'rüber#bringen* is added as *'rüberbringen* and *rüberbringen*.
+///   The full prefix is also added, *herüberbringen* ->
+///   *herüber#bringen*.
+///
+/// Alternatives without a separable prefix (e.g. *suchen* in
+/// *suchen|heim#suchen*) and annotations that are separated from the lemma
+/// by an underscore (e.g. *an#fangen_du_*) are ignored. Keys are
+/// lowercased.
+pub fn read_separable_verbs<R>(r: R) -> Result<HashMap<String, String>, LemmatizationError>
+where
+    R: BufRead,
+{
+    let mut verbs = HashMap::new();
+
+    for (idx, line) in r.lines().enumerate() {
+        let entries =
+            expand_line(&line?).map_err(|reason| LemmatizationError::InvalidLexiconEntry {
+                line: idx + 1,
+                reason,
+            })?;
+        verbs.extend(entries);
+    }
+
+    Ok(verbs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    use crate::LemmatizationError;
+
+    use super::{read_separable_verbs, SeparableVerbs};
+
+    #[test]
+    pub fn reads_separable_verbs() {
+        let f = File::open("testdata/separable-verbs.txt").unwrap();
+        let verbs = read_separable_verbs(BufReader::new(f)).unwrap();
+
+        let mut entries = verbs
+            .iter()
+            .map(|(key, lemma)| (key.as_str(), lemma.as_str()))
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("'naufsteigen", "'nauf#steigen"),
+                ("'rüberbringen", "'rüber#bringen"),
+                ("abnehmen", "ab#nehmen"),
+                ("anfangen", "an#fangen"),
+                ("heimsuchen", "heim#suchen"),
+                ("herüberbringen", "herüber#bringen"),
+                ("hinaufsteigen", "hinauf#steigen"),
+                ("naufsteigen", "'nauf#steigen"),
+                ("rüberbringen", "'rüber#bringen"),
+                ("umbauen", "um#bauen"),
+                ("wiederaufbauen", "wieder#auf#bauen"),
+                ("zunehmen", "zu#nehmen"),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn compiles_separable_verbs() {
+        let f = File::open("testdata/separable-verbs.txt").unwrap();
+        let verbs = SeparableVerbs::from(read_separable_verbs(BufReader::new(f)).unwrap());
+
+        assert_eq!(verbs.get("abnehmen").as_deref(), Some("ab#nehmen"));
+        assert_eq!(verbs.get("rüberbringen").as_deref(), Some("'rüber#bringen"));
+        assert!(verbs.contains("wiederaufbauen"));
+
+        // Verbs must match completely.
+        assert!(!verbs.contains("ab"));
+        assert!(!verbs.contains("abnehmenx"));
+        assert!(!verbs.contains("nehmen"));
+    }
+
+    #[test]
+    pub fn rejects_malformed_entries() {
+        for (lexicon, line) in &[
+            ("ab#zeichnen\nzeichnen\n", 2),
+            ("ab#zeichnen\nzeichnen|ab#zeichnen|\n", 2),
+            ("ab#zeichnen\n\n# Comment\nab##zeichnen\n", 4),
+            ("ab#\n", 1),
+            ("(ab#zeichnen\n", 1),
+            ("ab#zeichnen|'xüber#bringen\n", 1),
+            ("ab#zeichnen|ab#zeich nen\n", 1),
+        ] {
+            match read_separable_verbs(Cursor::new(lexicon)) {
+                Err(LemmatizationError::InvalidLexiconEntry { line: err_line, .. }) => {
+                    assert_eq!(err_line, *line, "Lexicon: {:?}", lexicon)
+                }
+                result => panic!(
+                    "Expected an invalid entry in {:?}, got {:?}",
+                    lexicon, result
+                ),
+            }
+        }
+    }
+}
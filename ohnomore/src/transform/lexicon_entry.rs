@@ -0,0 +1,137 @@
+//! Notation of separable verb lexicons.
+//!
+//! This module is also compiled into the build script, which compiles the
+//! bundled lexicon of separable verbs. It can therefore not use other
+//! parts of the crate.
+
+/// Separator of the verb and the lemma in compiled lexicon entries.
+pub(crate) const ENTRY_SEPARATOR: char = '\t';
+
+/// Expand a lexicon line into verbs and their lemmas with marked prefixes.
+///
+/// See `lexicon::read_separable_verbs` for the notation. Empty lines and
+/// comments do not have entries.
+pub(crate) fn expand_line(line: &str) -> Result<Vec<(String, String)>, String> {
+    let line = line.trim();
+
+    let mut entries = Vec::new();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(entries);
+    }
+
+    let alternatives = line.split('|').collect::<Vec<_>>();
+    for entry in &alternatives {
+        let lemma = entry.split('_').next().unwrap_or(entry);
+
+        // An alternative can be a verb without separable prefix, e.g.
+        // *suchen|heim#suchen*.
+        if alternatives.len() > 1 && is_word(lemma) {
+            continue;
+        }
+
+        for lemma in expand_entry(lemma)? {
+            let key = lemma.replace('#', "").to_lowercase();
+            if key.contains('\'') {
+                entries.push((key.replace('\'', ""), lemma.clone()));
+            }
+            entries.push((key, lemma));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Compile a verb and its lemma into a lexicon entry.
+///
+/// Since the separator sorts before all characters of a verb, the entries
+/// of a verb are contiguous in a sorted set.
+pub(crate) fn compile_entry(verb: &str, lemma: &str) -> String {
+    format!("{}{}{}", verb, ENTRY_SEPARATOR, lemma)
+}
+
+/// Expand an entry into lemmas with marked prefixes.
+fn expand_entry(entry: &str) -> Result<Vec<String>, String> {
+    let parts = entry.split('#').collect::<Vec<_>>();
+    let (verb, prefixes) = parts.split_last().expect("split returns at least one part");
+
+    if prefixes.is_empty() {
+        return Err(format!("verb without separable prefix: {}", entry));
+    }
+
+    if !is_word(verb) {
+        return Err(format!("invalid verb: {}", entry));
+    }
+
+    // Every prefix has one or more alternative spellings, an optional
+    // prefix can also be absent.
+    let mut expansions: Vec<Vec<String>> = vec![Vec::new()];
+    for &prefix in prefixes {
+        let alternatives = prefix_alternatives(prefix)
+            .ok_or_else(|| format!("invalid prefix '{}': {}", prefix, entry))?;
+
+        expansions = expansions
+            .into_iter()
+            .flat_map(|expansion| {
+                alternatives.iter().map(move |alternative| {
+                    let mut expansion = expansion.clone();
+                    expansion.extend(alternative.clone());
+                    expansion
+                })
+            })
+            .collect();
+    }
+
+    Ok(expansions
+        .into_iter()
+        .filter(|expansion| !expansion.is_empty())
+        .map(|mut expansion| {
+            expansion.push(verb.to_string());
+            expansion.join("#")
+        })
+        .collect())
+}
+
+/// Get the alternative spellings of a prefix.
+///
+/// An alternative is empty when the prefix is optional. Returns `None`
+/// when the prefix is malformed.
+fn prefix_alternatives(prefix: &str) -> Option<Vec<Option<String>>> {
+    if let Some(optional) = prefix
+        .strip_prefix('(')
+        .and_then(|prefix| prefix.strip_suffix(')'))
+    {
+        let mut alternatives = prefix_alternatives(optional)?;
+        alternatives.push(None);
+        return Some(alternatives);
+    }
+
+    if let Some(elided) = prefix.strip_prefix('\'') {
+        if !is_word(elided) {
+            return None;
+        }
+
+        // 'rüber -> herüber, 'nauf -> hinauf.
+        let full = if elided.starts_with('r') {
+            "he"
+        } else if elided.starts_with('n') {
+            "hi"
+        } else {
+            return None;
+        };
+
+        return Some(vec![
+            Some(prefix.to_owned()),
+            Some(format!("{}{}", full, elided)),
+        ]);
+    }
+
+    if is_word(prefix) {
+        Some(vec![Some(prefix.to_owned())])
+    } else {
+        None
+    }
+}
+
+fn is_word(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(char::is_alphabetic)
+}
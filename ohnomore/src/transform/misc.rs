@@ -3,6 +3,7 @@
 //! This module provides transformations that can be used for both
 //! lemmatization and delemmatization.
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 
 use fst::Set;
@@ -11,7 +12,8 @@ use maplit::{hashmap, hashset};
 
 use crate::automaton::LongestPrefix;
 use crate::constants::*;
-use crate::transform::{DependencyGraph, Transform};
+use crate::data::{bundled_fst, bundled_set};
+use crate::transform::{DependencyGraph, LemmaScope, Transform};
 
 /// Simplify article and relative pronoun lemmas.
 ///
@@ -24,7 +26,15 @@ use crate::transform::{DependencyGraph, Transform};
 pub struct SimplifyArticleLemma;
 
 impl Transform for SimplifyArticleLemma {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![ARTICLE_TAG, SUBST_REL_PRONOUN, ATTR_REL_PRONOUN])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
         let form = token.form();
@@ -32,28 +42,18 @@ impl Transform for SimplifyArticleLemma {
 
         if tag == ARTICLE_TAG || tag == SUBST_REL_PRONOUN || tag == ATTR_REL_PRONOUN {
             if form.to_lowercase().starts_with('d') {
-                return String::from("d");
+                return Cow::Borrowed("d");
             } else if form.to_lowercase().starts_with('e') {
-                return String::from("e");
+                return Cow::Borrowed("e");
             }
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
 lazy_static! {
-    static ref PIAT_PREFIXES: Set<Vec<u8>> = Set::from_iter(vec![
-        "einig",
-        "etlich",
-        "irgendein",
-        "irgendwelch",
-        "jedwed",
-        "kein",
-        "manch",
-        "wenig",
-    ])
-    .unwrap();
+    static ref PIAT_PREFIXES: Set<&'static [u8]> = bundled_set(bundled_fst!("piat-prefixes"));
 }
 
 /// Simplify attributing indefinite pronouns without determiner (PIAT)
@@ -71,51 +71,44 @@ lazy_static! {
 ///  "manch*" -> "manch"
 ///  "wenig*" -> "wenig"
 ///
-///
-
 pub struct SimplifyPIAT;
 impl Transform for SimplifyPIAT {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![ATTRIBUTING_INDEF_PRONOUN_WITHOUT_DET])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
         let form = token.form();
         let tag = token.xpos();
 
         if tag != ATTRIBUTING_INDEF_PRONOUN_WITHOUT_DET {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         let form = form.to_lowercase();
 
         if form == "keinerlei" {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         if let Some(prefix) = PIAT_PREFIXES.longest_prefix(&form) {
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
 lazy_static! {
-    static ref PIDAT_LONG_PREFIXES: Set<Vec<u8>> =
-        Set::from_iter(vec!["allermeisten", "jedwed", "wenigst"]).unwrap();
-    static ref PIDAT_PREFIXES: Set<Vec<u8>> = Set::from_iter(vec![
-        "all",
-        "ebensolch",
-        "ebensoviel",
-        "jed",
-        "jeglich",
-        "meist",
-        "solch",
-        "soviel",
-        "viel",
-        "wenig",
-        "zuviel",
-    ])
-    .unwrap();
+    static ref PIDAT_LONG_PREFIXES: Set<&'static [u8]> =
+        bundled_set(bundled_fst!("pidat-long-prefixes"));
+    static ref PIDAT_PREFIXES: Set<&'static [u8]> = bundled_set(bundled_fst!("pidat-prefixes"));
 }
 
 /// Simplify attributing indefinite pronouns with determiner (PIDAT)
@@ -139,67 +132,44 @@ lazy_static! {
 ///  "soviel*" -> "soviele"
 ///  "ebensoviel*" -> "ebensoviele"
 ///
-
 pub struct SimplifyPIDAT;
 impl Transform for SimplifyPIDAT {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![ATTRIBUTING_INDEF_PRONOUN_WITH_DET])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
         let form = token.form();
         let tag = token.xpos();
 
         if tag != ATTRIBUTING_INDEF_PRONOUN_WITH_DET {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         let form = form.to_lowercase();
 
         if let Some(prefix) = PIDAT_LONG_PREFIXES.longest_prefix(&form) {
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
         if let Some(prefix) = PIDAT_PREFIXES.longest_prefix(&form) {
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
 lazy_static! {
-    static ref PIS_LONG_PREFIXES: Set<Vec<u8>> = Set::from_iter(vec![
-        "alledem",
-        "allerhand",
-        "allerlei",
-        "allermeisten",
-        "einig",
-        "einzeln",
-        "einzig",
-        "jederman",
-        "wenigst",
-    ])
-    .unwrap();
-    static ref PIS_PREFIXES: Set<Vec<u8>> = Set::from_iter(vec![
-        "alle",
-        "ander",
-        "beid",
-        "ein",
-        "erster",
-        "etlich",
-        "etwas",
-        "irgendein",
-        "jed",
-        "kein",
-        "letzter",
-        "manch",
-        "meist",
-        "solch",
-        "soviel",
-        "viel",
-        "wenig",
-        "zuviel",
-    ])
-    .unwrap();
+    static ref PIS_LONG_PREFIXES: Set<&'static [u8]> =
+        bundled_set(bundled_fst!("pis-long-prefixes"));
+    static ref PIS_PREFIXES: Set<&'static [u8]> = bundled_set(bundled_fst!("pis-prefixes"));
 }
 
 /// Simplify attributing indefinite pronouns without determiner (PIAT)
@@ -207,35 +177,41 @@ lazy_static! {
 /// Simplifies lemmas of this class to some baseform (preliminary) based on matching
 /// lowercased prefixes of the forms. The rules are applied in the given order
 ///
-///
-
 pub struct SimplifyPIS;
 impl Transform for SimplifyPIS {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![SUBSTITUTING_INDEF_PRONOUN])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let lemma = token.lemma();
         let form = token.form();
         let tag = token.xpos();
 
         if tag != SUBSTITUTING_INDEF_PRONOUN {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         let form = form.to_lowercase();
 
         if form.starts_with("andr") {
-            return "ander".to_owned();
+            return Cow::Borrowed("ander");
         }
 
         if let Some(prefix) = PIS_LONG_PREFIXES.longest_prefix(&form) {
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
         if let Some(prefix) = PIS_PREFIXES.longest_prefix(&form) {
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
@@ -286,29 +262,37 @@ fn inside_out(map: &HashMap<&'static str, HashSet<&'static str>>) -> HashMap<Str
 pub struct SimplifyPersonalPronounLemma;
 
 impl Transform for SimplifyPersonalPronounLemma {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![PERSONAL_PRONOUN_TAG])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let tag = token.xpos();
         let lemma = token.lemma();
 
         if tag != PERSONAL_PRONOUN_TAG {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         let form = token.form().to_lowercase();
         if let Some(simplified_lemma) = PRONOUN_SIMPLIFICATIONS_LOOKUP.get(&form) {
-            simplified_lemma.to_owned()
+            Cow::Borrowed(simplified_lemma)
         } else {
-            lemma.to_owned()
+            Cow::Borrowed(lemma)
         }
     }
 }
 
 lazy_static! {
-    static ref ATTR_POSS_PRONOUN_PREFIXES: Set<Vec<u8>> =
-        Set::from_iter(vec!["dein", "euer", "eure", "ihr", "mein", "sein", "unser"]).unwrap();
-    static ref SUBST_POSS_PRONOUN_PREFIXES: Set<Vec<u8>> =
-        Set::from_iter(vec!["dein", "ihr", "mein", "sein", "unser", "unsrig"]).unwrap();
+    static ref ATTR_POSS_PRONOUN_PREFIXES: Set<&'static [u8]> =
+        bundled_set(bundled_fst!("attr-poss-pronoun-prefixes"));
+    static ref SUBST_POSS_PRONOUN_PREFIXES: Set<&'static [u8]> =
+        bundled_set(bundled_fst!("subst-poss-pronoun-prefixes"));
 }
 
 /// Simplify possesive pronoun lemmas.
@@ -321,14 +305,25 @@ lazy_static! {
 pub struct SimplifyPossesivePronounLemma;
 
 impl Transform for SimplifyPossesivePronounLemma {
-    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Token
+    }
+
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        Some(vec![
+            ATTRIBUTIVE_POSSESIVE_PRONOUN_TAG,
+            SUBST_POSSESIVE_PRONOUN_TAG,
+        ])
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
         let token = graph.token(node);
         let tag = token.xpos();
         let form = token.form();
         let lemma = token.lemma();
 
         if tag != ATTRIBUTIVE_POSSESIVE_PRONOUN_TAG && tag != SUBST_POSSESIVE_PRONOUN_TAG {
-            return lemma.to_owned();
+            return Cow::Borrowed(lemma);
         }
 
         let form = form.to_lowercase();
@@ -343,10 +338,10 @@ impl Transform for SimplifyPossesivePronounLemma {
                 prefix = "euer";
             }
 
-            return prefix.to_owned();
+            return Cow::Owned(prefix.to_owned());
         }
 
-        lemma.to_owned()
+        Cow::Borrowed(lemma)
     }
 }
 
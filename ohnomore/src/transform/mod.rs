@@ -1,8 +1,56 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
 use udgraph::graph::Sentence;
 
+use self::adjacency::IndexedGraph;
+
 #[allow(clippy::len_without_is_empty)]
 pub trait DependencyGraph {
-    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, String)> + 'a>;
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a>;
+
+    /// Get the head of a node and the relation to the head.
+    ///
+    /// The head is *0* for tokens that are attached to the root. The
+    /// default implementation searches the dependents of all nodes, graph
+    /// implementations should provide a more efficient lookup.
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        (0..self.len()).find_map(|head| {
+            self.dependents(head)
+                .find(|(dependent, _)| *dependent == idx)
+                .map(|(_, rel)| (head, rel))
+        })
+    }
+
+    /// Get the ancestors of a node, starting with its head.
+    ///
+    /// Each ancestor is returned with the relation of the preceding node
+    /// to the ancestor. The last ancestor is the root (*0*) when the node
+    /// is connected to the root.
+    fn ancestors<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        let mut node = idx;
+
+        // Limit the number of steps, in case the graph contains a cycle.
+        Box::new(
+            std::iter::from_fn(move || {
+                let (head, rel) = self.head(node)?;
+                node = head;
+                Some((head, rel))
+            })
+            .take(self.len()),
+        )
+    }
+
+    /// Get the siblings of a node with their relations to the shared head.
+    fn siblings<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        match self.head(idx) {
+            Some((head, _)) => Box::new(
+                self.dependents(head)
+                    .filter(move |(dependent, _)| *dependent != idx),
+            ),
+            None => Box::new(std::iter::empty()),
+        }
+    }
 
     fn token(&self, idx: usize) -> &dyn Token;
 
@@ -12,16 +60,12 @@ pub trait DependencyGraph {
 }
 
 impl DependencyGraph for Sentence {
-    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, String)> + 'a> {
-        Box::new(self.dep_graph().dependents(idx).map(|triple| {
-            (
-                triple.dependent(),
-                triple
-                    .relation()
-                    .expect("Edge without a dependency relation")
-                    .to_owned(),
-            )
-        }))
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(udgraph_edges::dependents(self, idx))
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        udgraph_edges::head(self, idx)
     }
 
     fn token(&self, idx: usize) -> &dyn Token {
@@ -41,8 +85,42 @@ impl DependencyGraph for Sentence {
     }
 }
 
+/// The tokens of a dependency graph in sentence order.
+///
+/// This view provides access to the linear order of tokens, for
+/// transformations that do not rely on the dependency structure. Tokens
+/// are numbered as in the graph, starting at *1*.
+#[derive(Clone, Copy)]
+pub struct TokenSequence<'a> {
+    graph: &'a dyn DependencyGraph,
+}
+
+impl<'a> TokenSequence<'a> {
+    pub fn new(graph: &'a dyn DependencyGraph) -> Self {
+        TokenSequence { graph }
+    }
+
+    /// Get the tokens that follow a token, closest first.
+    pub fn following(self, idx: usize) -> impl Iterator<Item = (usize, &'a dyn Token)> {
+        (idx + 1..self.graph.len()).map(move |idx| (idx, self.graph.token(idx)))
+    }
+
+    /// Get the tokens that precede a token, closest first.
+    pub fn preceding(self, idx: usize) -> impl Iterator<Item = (usize, &'a dyn Token)> {
+        (1..idx).rev().map(move |idx| (idx, self.graph.token(idx)))
+    }
+
+    /// Get all tokens in sentence order.
+    pub fn tokens(self) -> impl Iterator<Item = (usize, &'a dyn Token)> {
+        self.following(0)
+    }
+}
+
 pub trait TokenMut: Token {
     fn set_lemma(&mut self, lemma: Option<String>);
+
+    /// Set a feature in the MISC column of the token.
+    fn set_misc(&mut self, key: &str, value: String);
 }
 
 pub trait Token {
@@ -50,6 +128,14 @@ pub trait Token {
     fn lemma(&self) -> &str;
     fn upos(&self) -> &str;
     fn xpos(&self) -> &str;
+
+    /// Get the lemma, or `None` when the token does not have a lemma.
+    ///
+    /// `lemma` returns *_* for tokens without a lemma. This method can be
+    /// used to distinguish absent lemmas from the lemma *_*.
+    fn optional_lemma(&self) -> Option<&str> {
+        Some(self.lemma())
+    }
 }
 
 impl Token for udgraph::token::Token {
@@ -68,46 +154,631 @@ impl Token for udgraph::token::Token {
     fn xpos(&self) -> &str {
         self.xpos().unwrap()
     }
+
+    fn optional_lemma(&self) -> Option<&str> {
+        self.lemma()
+    }
 }
 
 impl TokenMut for udgraph::token::Token {
     fn set_lemma(&mut self, lemma: Option<String>) {
         self.set_lemma(lemma);
     }
+
+    fn set_misc(&mut self, key: &str, value: String) {
+        self.misc_mut().insert(key.to_owned(), Some(value));
+    }
+}
+
+/// The lemmas that a transformation reads.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LemmaScope {
+    /// The transformation only reads the lemma of the token that it
+    /// transforms.
+    Token,
+
+    /// The transformation can read the lemmas of other tokens in the graph,
+    /// such as the lemma of the head.
+    Graph,
 }
 
 pub trait Transform: Sync {
+    /// Get the lemmas that the transformation reads.
+    ///
+    /// `Transforms` applies consecutive transformations with the `Token`
+    /// scope in a single pass over the graph. The default implementation
+    /// returns `Graph`, which means that the transformation is only
+    /// applied after all tokens were processed by preceding
+    /// transformations.
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Graph
+    }
+
+    /// Get the tags that the transformation applies to.
+    ///
+    /// If a transformation only changes the lemmas of tokens with
+    /// particular tags (XPOS), it can return these tags, so that
+    /// `Transforms` can skip the transformation for tokens with other
+    /// tags. The default implementation returns `None`, which means that
+    /// the transformation is applied to tokens with any tag.
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        None
+    }
+
+    /// Get the transformed lemma of a token.
+    ///
+    /// Transformations should return a borrowed lemma when the lemma is
+    /// not changed, so that no allocations are necessary for tokens that
+    /// a transformation does not apply to.
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str>;
+}
+
+/// A transformation that returns an owned lemma.
+///
+/// This trait provides the `String`-based signature of earlier versions
+/// of `Transform`. Every `OwnedTransform` is also a `Transform`. Since
+/// the returned lemmas are always owned, `Transform` should be preferred
+/// for new transformations.
+pub trait OwnedTransform: Sync {
+    /// Get the tags that the transformation applies to.
+    ///
+    /// See `Transform::applicable_tags`.
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        None
+    }
+
+    /// Get the lemmas that the transformation reads.
+    ///
+    /// See `Transform::lemma_scope`.
+    fn lemma_scope(&self) -> LemmaScope {
+        LemmaScope::Graph
+    }
+
     fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String;
 }
 
+impl<T> Transform for T
+where
+    T: OwnedTransform,
+{
+    fn applicable_tags(&self) -> Option<Vec<&str>> {
+        OwnedTransform::applicable_tags(self)
+    }
+
+    fn lemma_scope(&self) -> LemmaScope {
+        OwnedTransform::lemma_scope(self)
+    }
+
+    fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+        Cow::Owned(OwnedTransform::transform(self, graph, node))
+    }
+}
+
 /// A list of `Transform`s.
-pub struct Transforms(pub Vec<Box<dyn Transform>>);
+///
+/// The list is constructed with `Transforms::new` or `From<Vec<_>>`, and
+/// the transformations can be retrieved with `transforms`. This replaces
+/// the public tuple field of earlier versions, since the list also holds
+/// an index of the transformations.
+pub struct Transforms {
+    transforms: Vec<Box<dyn Transform>>,
+
+    /// Tag -> indices of the transformations that apply to the tag.
+    tag_transforms: HashMap<String, Vec<usize>>,
+
+    /// Indices of the transformations that apply to all tags.
+    untagged_transforms: Vec<usize>,
+
+    /// Exclusive end indices of the stages of the transformation list.
+    /// The transformations of a stage are applied in a single pass.
+    stage_ends: Vec<usize>,
+}
 
 impl Transforms {
+    /// Construct a transformation list.
+    ///
+    /// The transformations are indexed by the tags that they apply to
+    /// (see `Transform::applicable_tags`). Consecutive transformations
+    /// with the `Token` lemma scope are grouped in a single stage (see
+    /// `Transform::lemma_scope`).
+    pub fn new(transforms: Vec<Box<dyn Transform>>) -> Self {
+        let mut tag_transforms: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut untagged_transforms = Vec::new();
+
+        for (idx, t) in transforms.iter().enumerate() {
+            match t.applicable_tags() {
+                Some(tags) => {
+                    for tag in tags {
+                        tag_transforms.entry(tag.to_owned()).or_default().push(idx);
+                    }
+                }
+                None => untagged_transforms.push(idx),
+            }
+        }
+
+        // Transformations without tags also apply to tokens with indexed
+        // tags. Sort the indices to retain the order of the transformations.
+        for indices in tag_transforms.values_mut() {
+            indices.extend(&untagged_transforms);
+            indices.sort_unstable();
+            indices.dedup();
+        }
+
+        // A transformation that reads the lemmas of other tokens ends the
+        // preceding stage and is put in a stage of its own.
+        let mut stage_ends = Vec::new();
+        for (idx, t) in transforms.iter().enumerate() {
+            if t.lemma_scope() == LemmaScope::Graph {
+                stage_ends.push(idx);
+                stage_ends.push(idx + 1);
+            }
+        }
+        stage_ends.push(transforms.len());
+        stage_ends.retain(|&end| end != 0);
+        stage_ends.dedup();
+
+        Transforms {
+            transforms,
+            tag_transforms,
+            untagged_transforms,
+            stage_ends,
+        }
+    }
+
+    /// Get the transformations.
+    pub fn transforms(&self) -> &[Box<dyn Transform>] {
+        &self.transforms
+    }
+
+    /// Get the transformations, consuming the list.
+    pub fn into_transforms(self) -> Vec<Box<dyn Transform>> {
+        self.transforms
+    }
+
     /// Transform a graph using the transformation list.
     ///
     /// This method applies the transformations to the given graph. Each
     /// transform is fully applied to the graph before the next transform,
     /// to ensure that dependencies between transforms are correctly handled.
+    /// Transformations are skipped for tokens with tags that they do not
+    /// apply to.
+    ///
+    /// Consecutive transformations that only read the lemma of the token
+    /// that they transform are applied in a single pass over the graph.
+    /// This gives the same result as applying them one by one.
+    ///
+    /// The dependency structure of the graph is indexed once and shared
+    /// by all transformations.
+    pub fn transform(&self, graph: &mut dyn DependencyGraph) {
+        let graph = &mut IndexedGraph::new(graph);
+
+        // Get the transformations that apply to each token. Since the
+        // transformations are applied in order, we only need to track the
+        // next applicable transformation of each token.
+        let token_transforms: Vec<&[usize]> = (1..graph.len())
+            .map(|idx| self.applicable_transforms(graph.token(idx).xpos()))
+            .collect();
+        let mut cursors = vec![0; token_transforms.len()];
+
+        for &stage_end in &self.stage_ends {
+            for (idx, (applicable, cursor)) in
+                (1..).zip(token_transforms.iter().zip(cursors.iter_mut()))
+            {
+                while let Some(&t_idx) = applicable.get(*cursor) {
+                    if t_idx >= stage_end {
+                        break;
+                    }
+
+                    *cursor += 1;
+                    apply_transform(self.transforms[t_idx].as_ref(), graph, idx);
+                }
+            }
+        }
+    }
+
+    fn applicable_transforms(&self, tag: &str) -> &[usize] {
+        self.tag_transforms
+            .get(tag)
+            .unwrap_or(&self.untagged_transforms)
+    }
+
+    /// Transform a graph, storing the lemmas in a MISC feature.
+    ///
+    /// This method applies the transformations in the same manner as
+    /// `transform`. However, the final transformed lemma of each token is
+    /// stored in the MISC feature `key`, while the lemma itself is
+    /// restored to the original lemma. Tokens without a lemma remain
+    /// without a lemma.
+    pub fn transform_to_misc(&self, graph: &mut dyn DependencyGraph, key: &str) {
+        let original_lemmas: Vec<_> = (1..graph.len())
+            .map(|idx| graph.token(idx).optional_lemma().map(ToOwned::to_owned))
+            .collect();
+
+        self.transform(graph);
+
+        for (idx, original_lemma) in (1..graph.len()).zip(original_lemmas) {
+            let token = graph.token_mut(idx);
+            if let Some(lemma) = token.optional_lemma().map(ToOwned::to_owned) {
+                token.set_misc(key, lemma);
+            }
+            token.set_lemma(original_lemma);
+        }
+    }
+}
+
+impl From<Vec<Box<dyn Transform>>> for Transforms {
+    fn from(transforms: Vec<Box<dyn Transform>>) -> Self {
+        Transforms::new(transforms)
+    }
+}
+
+impl From<Transforms> for Vec<Box<dyn Transform>> {
+    fn from(transforms: Transforms) -> Self {
+        transforms.into_transforms()
+    }
+}
+
+/// Apply a transformation to a token, updating its lemma.
+fn apply_transform(transform: &dyn Transform, graph: &mut dyn DependencyGraph, idx: usize) {
+    let lemma = match transform.transform(graph, idx) {
+        // Avoid updating lemmas that were not changed.
+        Cow::Borrowed(lemma) if lemma == graph.token(idx).lemma() => return,
+        Cow::Borrowed(lemma) => lemma.to_owned(),
+        Cow::Owned(lemma) => lemma,
+    };
+
+    graph.token_mut(idx).set_lemma(Some(lemma));
+}
+
+/// A transformation that adds a feature to the MISC column.
+///
+/// In contrast to `Transform`, this transformation does not change the
+/// lemma of a token, but adds a feature with the key returned by `key`.
+pub trait MiscTransform: Sync {
+    /// The key of the MISC feature.
+    fn key(&self) -> &str;
+
+    /// Get the value of the feature for a token.
+    ///
+    /// No feature is added when `None` is returned.
+    fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> Option<String>;
+}
+
+/// A list of `MiscTransform`s.
+///
+/// Like `Transforms`, the list is constructed with `MiscTransforms::new`
+/// or `From<Vec<_>>` rather than through a public tuple field.
+pub struct MiscTransforms {
+    transforms: Vec<Box<dyn MiscTransform>>,
+}
+
+impl MiscTransforms {
+    /// Construct a list of MISC transformations.
+    pub fn new(transforms: Vec<Box<dyn MiscTransform>>) -> Self {
+        MiscTransforms { transforms }
+    }
+
+    /// Get the transformations.
+    pub fn transforms(&self) -> &[Box<dyn MiscTransform>] {
+        &self.transforms
+    }
+
+    /// Get the transformations, consuming the list.
+    pub fn into_transforms(self) -> Vec<Box<dyn MiscTransform>> {
+        self.transforms
+    }
+
+    /// Add MISC features to a graph using the transformation list.
     pub fn transform(&self, graph: &mut dyn DependencyGraph) {
-        for t in &self.0 {
+        let graph = &mut IndexedGraph::new(graph);
+
+        for t in &self.transforms {
             for idx in 1..graph.len() {
-                let lemma = t.as_ref().transform(graph, idx);
-                graph.token_mut(idx).set_lemma(Some(lemma));
+                if let Some(value) = t.as_ref().transform(graph, idx) {
+                    graph.token_mut(idx).set_misc(t.key(), value);
+                }
             }
         }
     }
 }
 
+impl From<Vec<Box<dyn MiscTransform>>> for MiscTransforms {
+    fn from(transforms: Vec<Box<dyn MiscTransform>>) -> Self {
+        MiscTransforms::new(transforms)
+    }
+}
+
+impl From<MiscTransforms> for Vec<Box<dyn MiscTransform>> {
+    fn from(transforms: MiscTransforms) -> Self {
+        transforms.into_transforms()
+    }
+}
+
+mod adjacency;
+
+pub mod compound;
+
 pub mod delemmatization;
 
 pub mod lemmatization;
 
+pub mod lexicon;
+
+mod lexicon_entry;
+
 pub mod misc;
 
 mod named_entity;
 
+pub mod pipeline;
+
+pub mod simple;
+
 mod svp;
 
+#[cfg(any(test, feature = "test-cases"))]
+pub mod test_cases;
+
 #[cfg(test)]
 pub(crate) mod test_helpers;
+
+mod udgraph_edges;
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use udgraph::graph::{DepTriple, Sentence};
+    use udgraph::token::{Token, TokenBuilder};
+
+    use super::delemmatization::RemoveSepVerbPrefix;
+    use super::lemmatization::{FormAsLemma, RestoreCase};
+    use super::{
+        pipeline, DependencyGraph, LemmaScope, OwnedTransform, TokenSequence, Transform, Transforms,
+    };
+
+    fn test_sentence() -> Sentence {
+        let mut sentence = Sentence::new();
+        for &(form, lemma, xpos) in &[
+            ("Einige", "einige", "PIS"),
+            ("Bürger", "bürger", "NN"),
+            ("fingen", "fangen", "VVFIN"),
+            ("sich", "sich", "PRF"),
+            ("in", "in", "APPR"),
+            ("Ostberlin", "ostberlin", "NE"),
+            ("ihre", "ihr", "PPOSAT"),
+            ("Arbeit", "arbeit", "NN"),
+            ("an", "an", "PTKVZ"),
+            ("und", "und", "KON"),
+            ("wiedergutgemacht", "machen", "VVPP"),
+            (".", ".", "$."),
+        ] {
+            sentence.push(TokenBuilder::new(form).lemma(lemma).xpos(xpos).into());
+        }
+
+        sentence
+            .dep_graph_mut()
+            .add_deprel(DepTriple::new(3, Some("AVZ"), 9))
+            .unwrap();
+
+        sentence
+    }
+
+    /// Apply every transformation to every token.
+    fn transform_naive(transforms: &[Box<dyn Transform>], sentence: &mut Sentence) {
+        for t in transforms {
+            for idx in 1..sentence.len() {
+                let lemma = t.transform(sentence, idx).into_owned();
+                sentence[idx].token_mut().unwrap().set_lemma(Some(lemma));
+            }
+        }
+    }
+
+    /// Append the lemma of the preceding token to the lemma.
+    struct AppendPrecedingLemma;
+
+    impl Transform for AppendPrecedingLemma {
+        fn transform<'a>(&self, graph: &'a dyn DependencyGraph, node: usize) -> Cow<'a, str> {
+            let lemma = graph.token(node).lemma();
+
+            if node == 1 {
+                return Cow::Borrowed(lemma);
+            }
+
+            Cow::Owned(format!("{}+{}", lemma, graph.token(node - 1).lemma()))
+        }
+    }
+
+    struct UppercaseLemma;
+
+    impl OwnedTransform for UppercaseLemma {
+        fn lemma_scope(&self) -> LemmaScope {
+            LemmaScope::Token
+        }
+
+        fn transform(&self, graph: &dyn DependencyGraph, node: usize) -> String {
+            graph.token(node).lemma().to_uppercase()
+        }
+    }
+
+    /// A graph that uses the default implementations of `head`,
+    /// `ancestors`, and `siblings`.
+    struct DefaultNavigation(Sentence);
+
+    impl DependencyGraph for DefaultNavigation {
+        fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+            self.0.dependents(idx)
+        }
+
+        fn token(&self, idx: usize) -> &dyn super::Token {
+            DependencyGraph::token(&self.0, idx)
+        }
+
+        fn token_mut(&mut self, idx: usize) -> &mut dyn super::TokenMut {
+            DependencyGraph::token_mut(&mut self.0, idx)
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    #[test]
+    pub fn graph_navigation() {
+        // Ich habe mich gefreut.
+        let mut sentence = Sentence::new();
+        for &form in &["Ich", "habe", "mich", "gefreut"] {
+            sentence.push(TokenBuilder::new(form).into());
+        }
+        for &(head, rel, dependent) in &[
+            (0, "ROOT", 2),
+            (2, "SUBJ", 1),
+            (2, "AUX", 4),
+            (4, "OBJA", 3),
+        ] {
+            sentence
+                .dep_graph_mut()
+                .add_deprel(DepTriple::new(head, Some(rel), dependent))
+                .unwrap();
+        }
+
+        let default_navigation = DefaultNavigation(sentence.clone());
+        let graphs: [&dyn DependencyGraph; 2] = [&sentence, &default_navigation];
+
+        for graph in &graphs {
+            assert_eq!(graph.head(0), None);
+            assert_eq!(graph.head(2), Some((0, "ROOT")));
+            assert_eq!(graph.head(3), Some((4, "OBJA")));
+            assert_eq!(
+                graph.ancestors(3).collect::<Vec<_>>(),
+                vec![(4, "OBJA"), (2, "AUX"), (0, "ROOT")]
+            );
+            assert_eq!(graph.siblings(1).collect::<Vec<_>>(), vec![(4, "AUX")]);
+            assert_eq!(graph.siblings(2).count(), 0);
+            assert_eq!(graph.siblings(0).count(), 0);
+        }
+    }
+
+    #[test]
+    pub fn owned_transform_is_transform() {
+        let mut sentence = Sentence::new();
+        sentence.push(TokenBuilder::new("Autos").lemma("auto").xpos("NN").into());
+
+        let transforms = Transforms::new(vec![Box::new(UppercaseLemma)]);
+        transforms.transform(&mut sentence);
+
+        assert_eq!(sentence[1].token().unwrap().lemma(), Some("AUTO"));
+    }
+
+    #[test]
+    pub fn token_sequence_is_in_sentence_order() {
+        let sentence = test_sentence();
+        let sequence = TokenSequence::new(&sentence);
+
+        assert_eq!(
+            sequence.tokens().map(|(idx, _)| idx).collect::<Vec<_>>(),
+            (1..sentence.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sequence
+                .preceding(3)
+                .map(|(_, token)| token.form())
+                .collect::<Vec<_>>(),
+            vec!["Bürger", "Einige"]
+        );
+        assert_eq!(
+            sequence
+                .following(10)
+                .map(|(_, token)| token.form())
+                .collect::<Vec<_>>(),
+            vec!["wiedergutgemacht", "."]
+        );
+        assert_eq!(sequence.following(12).count(), 0);
+    }
+
+    #[test]
+    pub fn transform_equals_naive_transform() {
+        let mut naive_sentence = test_sentence();
+        transform_naive(&pipeline::lemmatization(), &mut naive_sentence);
+
+        let mut sentence = test_sentence();
+        Transforms::new(pipeline::lemmatization()).transform(&mut sentence);
+
+        assert_eq!(sentence, naive_sentence);
+        assert_eq!(sentence[3].token().unwrap().lemma(), Some("an#fangen"));
+    }
+
+    #[test]
+    pub fn transform_fuses_token_scope_transforms() {
+        let transforms: Vec<Box<dyn Transform>> = vec![
+            Box::new(UppercaseLemma),
+            Box::new(FormAsLemma),
+            Box::new(AppendPrecedingLemma),
+            Box::new(RestoreCase),
+            Box::new(UppercaseLemma),
+        ];
+
+        let mut naive_sentence = test_sentence();
+        transform_naive(&transforms, &mut naive_sentence);
+
+        let transforms = Transforms::new(transforms);
+        assert_eq!(transforms.stage_ends, vec![2, 3, 5]);
+
+        let mut sentence = test_sentence();
+        transforms.transform(&mut sentence);
+
+        assert_eq!(sentence, naive_sentence);
+        assert_eq!(sentence[2].token().unwrap().lemma(), Some("BÜRGER+EINIGE"));
+    }
+
+    #[test]
+    pub fn transform_retains_unchanged_lemmas() {
+        let mut sentence = Sentence::new();
+        sentence.push(TokenBuilder::new("schnell").xpos("ADJD").into());
+        sentence.push(
+            TokenBuilder::new("zeichnet")
+                .lemma("ab#zeichnen")
+                .xpos("VVFIN")
+                .into(),
+        );
+
+        let transforms = Transforms::new(vec![Box::new(RemoveSepVerbPrefix)]);
+        transforms.transform(&mut sentence);
+
+        // A lemma is only set when the transformation changes it.
+        assert_eq!(sentence[1].token().unwrap().lemma(), None);
+        assert_eq!(sentence[2].token().unwrap().lemma(), Some("zeichnen"));
+    }
+
+    #[test]
+    pub fn transform_to_misc_retains_lemmas() {
+        let mut sentence = Sentence::new();
+        sentence.push(TokenBuilder::new("Und").lemma("Und").xpos("KON").into());
+        sentence.push(TokenBuilder::new("Autos").lemma("auto").xpos("NN").into());
+
+        let transforms = Transforms::new(vec![Box::new(FormAsLemma), Box::new(RestoreCase)]);
+        transforms.transform_to_misc(&mut sentence, "TuebaLemma");
+
+        let tokens: Vec<&Token> = sentence.iter().filter_map(|node| node.token()).collect();
+        assert_eq!(tokens[0].lemma(), Some("Und"));
+        assert_eq!(tokens[0].misc()["TuebaLemma"], Some("und".to_owned()));
+        assert_eq!(tokens[1].lemma(), Some("auto"));
+        assert_eq!(tokens[1].misc()["TuebaLemma"], Some("Auto".to_owned()));
+    }
+
+    #[test]
+    pub fn transform_to_misc_retains_absent_lemmas() {
+        let mut sentence = Sentence::new();
+        sentence.push(TokenBuilder::new("Und").xpos("KON").into());
+        sentence.push(TokenBuilder::new("Autos").xpos("NN").into());
+
+        let transforms = Transforms::new(vec![Box::new(FormAsLemma)]);
+        transforms.transform_to_misc(&mut sentence, "TuebaLemma");
+
+        let tokens: Vec<&Token> = sentence.iter().filter_map(|node| node.token()).collect();
+        assert_eq!(tokens[0].lemma(), None);
+        assert_eq!(tokens[0].misc()["TuebaLemma"], Some("und".to_owned()));
+        assert_eq!(tokens[1].lemma(), None);
+        assert!(!tokens[1].misc().contains_key("TuebaLemma"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Default transformation pipelines.
+//!
+//! This module provides the lists of transformations that are used by
+//! `ohnomore-preproc` and `ohnomore-postproc`, and the construction of
+//! transformations by name.
+
+use crate::data::DataBundle;
+use crate::transform::delemmatization::{
+    RemoveAlternatives, RemoveReflexiveTag, RemoveSepVerbPrefix, RemoveSeparatedVerbPrefix,
+    RemoveTruncMarker,
+};
+use crate::transform::lemmatization::{
+    AddContextualReflexiveTag, AddReflexiveTag, AddSeparableVerbPrefixes, AddSeparatedVerbPrefix,
+    AddSeparatedVerbPrefixLinear, FormAsLemma, MarkVerbPrefix, ReflexiveConfidence, RestoreCase,
+};
+use crate::transform::misc::{
+    SimplifyArticleLemma, SimplifyPIAT, SimplifyPIDAT, SimplifyPIS, SimplifyPersonalPronounLemma,
+    SimplifyPossesivePronounLemma,
+};
+use crate::transform::Transform;
+
+/// A transformation name with a constructor of the transformation.
+///
+/// The constructor uses the lexicons of the given data bundle.
+pub type NamedTransform = (&'static str, fn(&DataBundle) -> Box<dyn Transform>);
+
+/// Build a table of named transformations.
+///
+/// The table starts with the name that constructors use for the data
+/// bundle. Every entry is the type name of a transformation, optionally
+/// followed by an expression that constructs the transformation. Unit
+/// structs do not need an expression. The name is derived from the type,
+/// so a table with an unknown transformation does not compile.
+macro_rules! named_transforms {
+    (@construct $name:ident) => {
+        $name
+    };
+    (@construct $name:ident $transform:expr) => {
+        $transform
+    };
+    ($data:ident; $($name:ident $(=> $transform:expr)?,)*) => {
+        &[$((stringify!($name), {
+            #[allow(unused_variables)]
+            fn construct($data: &DataBundle) -> Box<dyn Transform> {
+                let transform: $name = named_transforms!(@construct $name $($transform)?);
+                Box::new(transform)
+            }
+
+            construct
+        }),)*]
+    };
+}
+
+/// Transformations from TüBa-D/Z lemmas to lemmas for machine learning.
+pub static DELEMMATIZATION_TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    RemoveAlternatives,
+    RemoveReflexiveTag,
+    RemoveSepVerbPrefix,
+    RemoveTruncMarker,
+    SimplifyArticleLemma,
+    SimplifyPossesivePronounLemma,
+    FormAsLemma,
+];
+
+/// Delemmatization transformations that retain attached prefixes.
+static ATTACHED_PREFIX_DELEMMATIZATION_TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    RemoveAlternatives,
+    RemoveReflexiveTag,
+    RemoveSeparatedVerbPrefix,
+    RemoveTruncMarker,
+    SimplifyArticleLemma,
+    SimplifyPossesivePronounLemma,
+    FormAsLemma,
+];
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas.
+pub static LEMMATIZATION_TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    FormAsLemma,
+    RestoreCase,
+    AddReflexiveTag,
+    AddSeparatedVerbPrefix => AddSeparatedVerbPrefix::new(true),
+    MarkVerbPrefix => MarkVerbPrefix::with_data(data),
+    SimplifyArticleLemma,
+    SimplifyPossesivePronounLemma,
+    SimplifyPIS,
+    SimplifyPIDAT,
+    SimplifyPIAT,
+];
+
+/// Lemmatization transformations that combine separated and attached
+/// prefixes.
+static COMBINED_PREFIX_LEMMATIZATION_TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    FormAsLemma,
+    RestoreCase,
+    AddReflexiveTag,
+    AddSeparableVerbPrefixes => AddSeparableVerbPrefixes::with_mark_verb_prefix(
+        MarkVerbPrefix::with_data(data),
+        true,
+    ),
+    SimplifyArticleLemma,
+    SimplifyPossesivePronounLemma,
+    SimplifyPIS,
+    SimplifyPIDAT,
+    SimplifyPIAT,
+];
+
+/// Lemmatization transformations for input without dependency relations.
+static LEMMATIZATION_WITHOUT_PARSE_TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    FormAsLemma,
+    RestoreCase,
+    AddReflexiveTag,
+    AddSeparatedVerbPrefixLinear => AddSeparatedVerbPrefixLinear::new(true),
+    MarkVerbPrefix => MarkVerbPrefix::with_data(data),
+    SimplifyArticleLemma,
+    SimplifyPossesivePronounLemma,
+    SimplifyPIS,
+    SimplifyPIDAT,
+    SimplifyPIAT,
+];
+
+/// Transformations that can be constructed by name.
+///
+/// Transformations are constructed with the settings of the default
+/// pipelines. `AddContextualReflexiveTag` uses high confidence.
+pub static TRANSFORMS: &[NamedTransform] = named_transforms![data;
+    AddContextualReflexiveTag => AddContextualReflexiveTag::with_data(
+        ReflexiveConfidence::High,
+        data,
+    ),
+    AddReflexiveTag,
+    AddSeparableVerbPrefixes => AddSeparableVerbPrefixes::with_mark_verb_prefix(
+        MarkVerbPrefix::with_data(data),
+        true,
+    ),
+    AddSeparatedVerbPrefix => AddSeparatedVerbPrefix::new(true),
+    AddSeparatedVerbPrefixLinear => AddSeparatedVerbPrefixLinear::new(true),
+    FormAsLemma,
+    MarkVerbPrefix => MarkVerbPrefix::with_data(data),
+    RemoveAlternatives,
+    RemoveReflexiveTag,
+    RemoveSepVerbPrefix,
+    RemoveSeparatedVerbPrefix,
+    RemoveTruncMarker,
+    RestoreCase,
+    SimplifyArticleLemma,
+    SimplifyPIAT,
+    SimplifyPIDAT,
+    SimplifyPIS,
+    SimplifyPersonalPronounLemma,
+    SimplifyPossesivePronounLemma,
+];
+
+/// Transformations from TüBa-D/Z lemmas to lemmas for machine learning.
+pub fn delemmatization() -> Vec<Box<dyn Transform>> {
+    construct(DELEMMATIZATION_TRANSFORMS, &DataBundle::default())
+}
+
+/// Transformations from TüBa-D/Z lemmas to lemmas for machine learning,
+/// retaining separable verb prefixes that are attached to the form.
+///
+/// This pipeline uses `RemoveSeparatedVerbPrefix` rather than
+/// `RemoveSepVerbPrefix`.
+pub fn delemmatization_retaining_attached_prefixes() -> Vec<Box<dyn Transform>> {
+    construct(
+        ATTACHED_PREFIX_DELEMMATIZATION_TRANSFORMS,
+        &DataBundle::default(),
+    )
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas.
+pub fn lemmatization() -> Vec<Box<dyn Transform>> {
+    lemmatization_with_data(&DataBundle::default())
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas, using the
+/// lexicons of the given data bundle.
+pub fn lemmatization_with_data(data: &DataBundle) -> Vec<Box<dyn Transform>> {
+    construct(LEMMATIZATION_TRANSFORMS, data)
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas, combining
+/// separated and attached prefixes.
+///
+/// This pipeline uses `AddSeparableVerbPrefixes` rather than
+/// `AddSeparatedVerbPrefix` followed by `MarkVerbPrefix`, so that a verb
+/// with separated and attached prefixes gets all prefixes, e.g.
+/// *wieder#an#fangen* for *anfing* in *als sie wieder anfing*. The order
+/// of alternatives differs from `lemmatization`: separated prefixes are
+/// used in sentence order, e.g. *ab#nehmen|zu#nehmen* rather than
+/// *zu#nehmen|ab#nehmen* for *nimmt eher ab als zu*.
+pub fn lemmatization_combining_prefixes() -> Vec<Box<dyn Transform>> {
+    lemmatization_combining_prefixes_with_data(&DataBundle::default())
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas, combining
+/// separated and attached prefixes, using the lexicons of the given data
+/// bundle.
+///
+/// See `lemmatization_combining_prefixes`.
+pub fn lemmatization_combining_prefixes_with_data(data: &DataBundle) -> Vec<Box<dyn Transform>> {
+    construct(COMBINED_PREFIX_LEMMATIZATION_TRANSFORMS, data)
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas, for input
+/// without dependency relations.
+///
+/// This pipeline uses `AddSeparatedVerbPrefixLinear` rather than
+/// `AddSeparatedVerbPrefix`.
+pub fn lemmatization_without_parse() -> Vec<Box<dyn Transform>> {
+    lemmatization_without_parse_with_data(&DataBundle::default())
+}
+
+/// Transformations from predicted lemmas to TüBa-D/Z lemmas, for input
+/// without dependency relations, using the lexicons of the given data
+/// bundle.
+pub fn lemmatization_without_parse_with_data(data: &DataBundle) -> Vec<Box<dyn Transform>> {
+    construct(LEMMATIZATION_WITHOUT_PARSE_TRANSFORMS, data)
+}
+
+fn construct(transforms: &[NamedTransform], data: &DataBundle) -> Vec<Box<dyn Transform>> {
+    transforms
+        .iter()
+        .map(|(_, constructor)| constructor(data))
+        .collect()
+}
+
+/// Names of the transformations that can be constructed with
+/// `transform_by_name`.
+pub fn transform_names() -> impl Iterator<Item = &'static str> {
+    TRANSFORMS.iter().map(|&(name, _)| name)
+}
+
+/// Construct a transformation by its type name.
+///
+/// See `TRANSFORMS` for the settings of the transformations. Returns
+/// `None` if there is no transformation with the given name.
+pub fn transform_by_name(name: &str) -> Option<Box<dyn Transform>> {
+    transform_by_name_with_data(name, &DataBundle::default())
+}
+
+/// Construct a transformation by its type name, using the lexicons of the
+/// given data bundle.
+///
+/// See `transform_by_name`.
+pub fn transform_by_name_with_data(name: &str, data: &DataBundle) -> Option<Box<dyn Transform>> {
+    TRANSFORMS
+        .iter()
+        .find(|&&(transform_name, _)| transform_name == name)
+        .map(|(_, constructor)| constructor(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{
+        transform_by_name, transform_names, ATTACHED_PREFIX_DELEMMATIZATION_TRANSFORMS,
+        COMBINED_PREFIX_LEMMATIZATION_TRANSFORMS, DELEMMATIZATION_TRANSFORMS,
+        LEMMATIZATION_TRANSFORMS, LEMMATIZATION_WITHOUT_PARSE_TRANSFORMS,
+    };
+
+    #[test]
+    pub fn all_transforms_can_be_constructed_by_name() {
+        for name in transform_names() {
+            assert!(
+                transform_by_name(name).is_some(),
+                "Unknown transform: {}",
+                name
+            );
+        }
+
+        assert!(transform_by_name("RemoveEverything").is_none());
+    }
+
+    #[test]
+    pub fn pipeline_transforms_can_be_constructed_by_name() {
+        let names = transform_names().collect::<HashSet<_>>();
+
+        for pipeline in &[
+            DELEMMATIZATION_TRANSFORMS,
+            ATTACHED_PREFIX_DELEMMATIZATION_TRANSFORMS,
+            LEMMATIZATION_TRANSFORMS,
+            COMBINED_PREFIX_LEMMATIZATION_TRANSFORMS,
+            LEMMATIZATION_WITHOUT_PARSE_TRANSFORMS,
+        ] {
+            for (name, _) in pipeline.iter() {
+                assert!(names.contains(name), "Unknown transform: {}", name);
+            }
+        }
+    }
+}
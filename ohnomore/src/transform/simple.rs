@@ -0,0 +1,229 @@
+//! Simple in-memory dependency graphs.
+//!
+//! This module provides a lightweight implementation of `DependencyGraph`,
+//! which can be used to apply transformations to sentences that are not
+//! read from CoNLL-U.
+
+use std::collections::BTreeMap;
+
+use crate::transform::{DependencyGraph, Token, TokenMut};
+
+/// A token of a `SimpleSentence`.
+///
+/// Fields that are not set are returned as *_*.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimpleToken {
+    form: String,
+    lemma: Option<String>,
+    upos: Option<String>,
+    xpos: Option<String>,
+    misc: BTreeMap<String, String>,
+}
+
+impl SimpleToken {
+    /// Construct a token with the given form.
+    pub fn new(form: impl Into<String>) -> Self {
+        SimpleToken {
+            form: form.into(),
+            lemma: None,
+            upos: None,
+            xpos: None,
+            misc: BTreeMap::new(),
+        }
+    }
+
+    /// Set the lemma of the token.
+    pub fn with_lemma(mut self, lemma: impl Into<String>) -> Self {
+        self.lemma = Some(lemma.into());
+        self
+    }
+
+    /// Set the universal part-of-speech tag of the token.
+    pub fn with_upos(mut self, upos: impl Into<String>) -> Self {
+        self.upos = Some(upos.into());
+        self
+    }
+
+    /// Set the language-specific part-of-speech tag of the token.
+    pub fn with_xpos(mut self, xpos: impl Into<String>) -> Self {
+        self.xpos = Some(xpos.into());
+        self
+    }
+
+    /// Get the MISC features of the token.
+    pub fn misc(&self) -> &BTreeMap<String, String> {
+        &self.misc
+    }
+}
+
+impl Token for SimpleToken {
+    fn form(&self) -> &str {
+        &self.form
+    }
+
+    fn lemma(&self) -> &str {
+        self.lemma.as_deref().unwrap_or("_")
+    }
+
+    fn upos(&self) -> &str {
+        self.upos.as_deref().unwrap_or("_")
+    }
+
+    fn xpos(&self) -> &str {
+        self.xpos.as_deref().unwrap_or("_")
+    }
+
+    fn optional_lemma(&self) -> Option<&str> {
+        self.lemma.as_deref()
+    }
+}
+
+impl TokenMut for SimpleToken {
+    fn set_lemma(&mut self, lemma: Option<String>) {
+        self.lemma = lemma;
+    }
+
+    fn set_misc(&mut self, key: &str, value: String) {
+        self.misc.insert(key.to_owned(), value);
+    }
+}
+
+/// A sentence with tokens and dependency relations.
+///
+/// Nodes are numbered as in CoNLL-U: node *0* is the artificial root and
+/// the tokens are numbered from *1*.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SimpleSentence {
+    tokens: Vec<SimpleToken>,
+
+    /// The head and relation of each token.
+    heads: Vec<Option<(usize, String)>>,
+}
+
+impl SimpleSentence {
+    /// Construct an empty sentence.
+    pub fn new() -> Self {
+        SimpleSentence::default()
+    }
+
+    /// Add a token to the sentence, returning its node index.
+    pub fn push(&mut self, token: SimpleToken) -> usize {
+        self.tokens.push(token);
+        self.heads.push(None);
+        self.tokens.len()
+    }
+
+    /// Attach `dependent` to `head` with the relation `relation`.
+    ///
+    /// A token has at most one head, so an existing relation of
+    /// `dependent` is replaced.
+    ///
+    /// Panics when `head` or `dependent` is not a node of the sentence,
+    /// or when `dependent` is the root.
+    pub fn add_dependency(&mut self, head: usize, relation: impl Into<String>, dependent: usize) {
+        assert!(head <= self.tokens.len(), "Unknown head: {}", head);
+        assert!(
+            dependent != 0 && dependent <= self.tokens.len(),
+            "Invalid dependent: {}",
+            dependent
+        );
+
+        self.heads[dependent - 1] = Some((head, relation.into()));
+    }
+
+    /// Get the tokens of the sentence.
+    pub fn tokens(&self) -> &[SimpleToken] {
+        &self.tokens
+    }
+}
+
+impl DependencyGraph for SimpleSentence {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(
+            self.heads
+                .iter()
+                .enumerate()
+                .filter_map(move |(dependent, head)| match head {
+                    Some((head, relation)) if *head == idx => {
+                        Some((dependent + 1, relation.as_str()))
+                    }
+                    _ => None,
+                }),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        idx.checked_sub(1)
+            .and_then(|idx| self.heads[idx].as_ref())
+            .map(|(head, relation)| (*head, relation.as_str()))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        assert!(idx != 0, "The root node was used as a token");
+        &self.tokens[idx - 1]
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        assert!(idx != 0, "The root node was used as a token");
+        &mut self.tokens[idx - 1]
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transform::{pipeline, DependencyGraph, Token, Transforms};
+
+    use super::{SimpleSentence, SimpleToken};
+
+    fn test_sentence() -> SimpleSentence {
+        let mut sentence = SimpleSentence::new();
+        let er = sentence.push(SimpleToken::new("Er").with_lemma("er").with_xpos("PPER"));
+        let faengt = sentence.push(
+            SimpleToken::new("fängt")
+                .with_lemma("fangen")
+                .with_xpos("VVFIN"),
+        );
+        let an = sentence.push(SimpleToken::new("an").with_lemma("an").with_xpos("PTKVZ"));
+        sentence.add_dependency(0, "ROOT", faengt);
+        sentence.add_dependency(faengt, "SUBJ", er);
+        sentence.add_dependency(faengt, "AVZ", an);
+        sentence
+    }
+
+    #[test]
+    pub fn simple_sentence_has_dependents() {
+        let sentence = test_sentence();
+        assert_eq!(sentence.len(), 4);
+        assert_eq!(
+            sentence.dependents(2).collect::<Vec<_>>(),
+            vec![(1, "SUBJ"), (3, "AVZ")]
+        );
+        assert_eq!(sentence.dependents(3).count(), 0);
+        assert_eq!(sentence.head(3), Some((2, "AVZ")));
+        assert_eq!(sentence.head(0), None);
+    }
+
+    #[test]
+    pub fn simple_sentence_can_be_transformed() {
+        let mut sentence = test_sentence();
+
+        let transforms = Transforms::new(pipeline::lemmatization());
+        transforms.transform_to_misc(&mut sentence, "TuebaLemma");
+
+        let verb = &sentence.tokens()[1];
+        assert_eq!(verb.lemma(), "fangen");
+        assert_eq!(verb.misc()["TuebaLemma"], "an#fangen");
+    }
+
+    #[test]
+    pub fn missing_fields_are_underscores() {
+        let token = SimpleToken::new("Haus");
+        assert_eq!(token.lemma(), "_");
+        assert_eq!(token.upos(), "_");
+        assert_eq!(token.xpos(), "_");
+    }
+}
@@ -1,86 +1,75 @@
 use std::cmp::Ordering;
-use std::collections::VecDeque;
 
 use fst::Set;
 
-use crate::automaton::Prefixes;
+use crate::automaton::PrefixStar;
 use crate::constants::*;
 
-/// Candidate list of prefixes and the corresponding stripped form.
-struct PrefixesCandidate<'a> {
-    stripped_form: &'a str,
-    prefixes: Vec<String>,
-}
-
-/// Look for all matches of (prefix)* in the given form. Ideally,
-/// we'd construct a Kleene star automaton of the prefix automaton.
-/// Unfortunately, this functionality is not (yet) provided by the
-/// fst crate. Instead, we repeatedly search prefixes in the set.
-fn prefix_star<'a, D>(prefix_set: &Set<D>, s: &'a str) -> Vec<PrefixesCandidate<'a>>
+pub fn longest_prefixes<D, F, L, T>(prefix_set: &Set<D>, form: F, lemma: L, tag: T) -> Vec<String>
 where
     D: AsRef<[u8]>,
+    F: AsRef<str>,
+    L: AsRef<str>,
+    T: AsRef<str>,
 {
-    let mut result = Vec::new();
-
-    let mut q = VecDeque::new();
-    q.push_back(PrefixesCandidate {
-        stripped_form: s,
-        prefixes: Vec::new(),
-    });
+    let lemma = lemma.as_ref();
+    let form = form.as_ref();
+    let tag = tag.as_ref();
 
-    while let Some(PrefixesCandidate {
-        stripped_form,
-        prefixes,
-    }) = q.pop_front()
-    {
-        result.push(PrefixesCandidate {
-            stripped_form,
-            prefixes: prefixes.clone(),
-        });
+    // Find the best match of (prefix)* in the form. Candidates are
+    // represented by the end offsets of their prefixes. Since we only
+    // retain the best candidate, the buffer of the best candidate is
+    // reused.
+    let mut best = Vec::new();
+    prefix_set.prefix_star(form, |offsets| {
+        if !is_valid_candidate(form, lemma, tag, offsets) {
+            return;
+        }
 
-        for prefix in prefix_set.prefixes(stripped_form) {
-            let mut prefixes = prefixes.clone();
-            let prefix_len = prefix.len();
-            prefixes.push(prefix.to_owned());
-            q.push_back(PrefixesCandidate {
-                stripped_form: &stripped_form[prefix_len..],
-                prefixes,
-            });
+        // Prefer the candidate that strips the longest prefix, then the
+        // candidate with the fewest prefixes. When candidates are equal,
+        // the last candidate is used, which is the candidate with the
+        // longest leftmost prefixes.
+        let ordering = stripped_len(offsets)
+            .cmp(&stripped_len(&best))
+            .then_with(|| best.len().cmp(&offsets.len()));
+        if ordering != Ordering::Less {
+            best.clear();
+            best.extend_from_slice(offsets);
         }
-    }
+    });
 
-    result
+    prefixes(form, &best).map(ToOwned::to_owned).collect()
 }
 
-pub fn longest_prefixes<D, F, L, T>(prefix_set: &Set<D>, form: F, lemma: L, tag: T) -> Vec<String>
+/// Find the prefixes of a lemma.
+///
+/// The remainder of the lemma after the prefixes must be a base verb
+/// according to `is_base_verb`. If there are multiple analyses, the
+/// analysis that strips the longest prefix is preferred, then the
+/// analysis with the fewest prefixes.
+pub fn lemma_prefixes<D, F>(prefix_set: &Set<D>, lemma: &str, is_base_verb: F) -> Vec<String>
 where
     D: AsRef<[u8]>,
-    F: AsRef<str>,
-    L: AsRef<str>,
-    T: AsRef<str>,
+    F: Fn(&str) -> bool,
 {
-    let lemma = lemma.as_ref();
-    let form = form.as_ref();
-    let tag = tag.as_ref();
-
-    let all_prefixes = prefix_star(prefix_set, form);
+    let mut best = Vec::new();
+    prefix_set.prefix_star(lemma, |offsets| {
+        let stripped_lemma = &lemma[stripped_len(offsets)..];
+        if offsets.is_empty() || !is_verb(stripped_lemma) || !is_base_verb(stripped_lemma) {
+            return;
+        }
 
-    FilterPrefixes {
-        inner: all_prefixes.into_iter(),
-        lemma,
-        tag,
-    }
-    .max_by(|l, r| {
-        match l.stripped_form.len().cmp(&r.stripped_form.len()) {
-            Ordering::Less => return Ordering::Greater,
-            Ordering::Greater => return Ordering::Less,
-            Ordering::Equal => (),
+        let ordering = stripped_len(offsets)
+            .cmp(&stripped_len(&best))
+            .then_with(|| best.len().cmp(&offsets.len()));
+        if ordering != Ordering::Less {
+            best.clear();
+            best.extend_from_slice(offsets);
         }
+    });
 
-        l.prefixes.len().cmp(&r.prefixes.len()).reverse()
-    })
-    .map(|t| t.prefixes)
-    .unwrap_or_else(Vec::new)
+    prefixes(lemma, &best).map(ToOwned::to_owned).collect()
 }
 
 fn is_verb<S>(verb: S) -> bool
@@ -91,52 +80,35 @@ where
     verb.as_ref().len() > 2
 }
 
-struct FilterPrefixes<'a, I>
-where
-    I: Iterator<Item = PrefixesCandidate<'a>>,
-{
-    lemma: &'a str,
-    tag: &'a str,
-    inner: I,
-}
+fn is_valid_candidate(form: &str, lemma: &str, tag: &str, offsets: &[usize]) -> bool {
+    let last_offset = ok_or!(offsets.last(), return true);
+    let last_prefix = prefixes(form, offsets).last().unwrap();
+    let stripped_form = &form[*last_offset..];
 
-impl<'a, I> Iterator for FilterPrefixes<'a, I>
-where
-    I: Iterator<Item = PrefixesCandidate<'a>>,
-{
-    type Item = PrefixesCandidate<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(candidate) = self.inner.next() {
-            if candidate.prefixes.is_empty() {
-                return Some(candidate);
-            }
-
-            // I don't like the to_owned() here, but as of 1.14-nightly, the
-            // borrows checker is not happy about moving candidate otherwise.
-            let last_prefix = candidate.prefixes.last().unwrap().to_owned();
-
-            // Avoid e.g. 'dazu' as a valid prefix for a zu-infinitive.
-            if self.tag == ZU_INFINITIVE_VERB
-                && last_prefix.ends_with("zu")
-                && !candidate.stripped_form.starts_with("zu")
-            {
-                continue;
-            }
+    // Avoid e.g. 'dazu' as a valid prefix for a zu-infinitive.
+    if tag == ZU_INFINITIVE_VERB && last_prefix.ends_with("zu") && !stripped_form.starts_with("zu")
+    {
+        return false;
+    }
 
-            // 1. Do not start stripping parts of the lemma
-            // 2. Prefix should not end with lemma. E.g.:
-            //    abgefangen fangen -> ab#fangen, not: ab#gefangen#fangen
-            if candidate.prefixes.iter().any(|p| self.lemma.starts_with(p))
-                || last_prefix.ends_with(&self.lemma)
-                || !is_verb(candidate.stripped_form)
-            {
-                continue;
-            }
+    // 1. Do not start stripping parts of the lemma
+    // 2. Prefix should not end with lemma. E.g.:
+    //    abgefangen fangen -> ab#fangen, not: ab#gefangen#fangen
+    !(prefixes(form, offsets).any(|p| lemma.starts_with(p))
+        || last_prefix.ends_with(lemma)
+        || !is_verb(stripped_form))
+}
 
-            return Some(candidate);
-        }
+/// Get the prefixes of a candidate.
+fn prefixes<'a>(form: &'a str, offsets: &'a [usize]) -> impl Iterator<Item = &'a str> {
+    offsets.iter().scan(0, move |start, &end| {
+        let prefix = &form[*start..end];
+        *start = end;
+        Some(prefix)
+    })
+}
 
-        None
-    }
+/// Get the length of the prefixes of a candidate.
+fn stripped_len(offsets: &[usize]) -> usize {
+    offsets.last().cloned().unwrap_or(0)
 }
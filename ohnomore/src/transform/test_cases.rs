@@ -0,0 +1,344 @@
+//! Test cases for transformations.
+//!
+//! This module reads test cases in a compact line-based format. Each
+//! non-empty line that does not start with *#* is a test case:
+//!
+//! ```text
+//! form lemma upos xpos transformed [rel head_form head_lemma head_upos head_xpos]
+//!   [rel dep_form dep_lemma dep_upos dep_xpos]*
+//! ```
+//!
+//! *transformed* is the expected output of the transformation. The token
+//! can optionally have a head and dependents. Since dependents can only be
+//! specified after the head, a single *-* in place of the head marks a
+//! token without a head:
+//!
+//! ```text
+//! _ zeichnen _ VVFIN ab#zeichnen - AVZ ab _ _ PTKVZ
+//! ```
+//!
+//! Note that *_ _ _ _ _* is not a missing head, but a head with empty
+//! fields.
+
+use std::borrow::Cow;
+use std::io::BufRead;
+
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+use crate::transform::simple::SimpleToken;
+use crate::transform::{DependencyGraph, MiscTransform, Token, TokenMut, Transform};
+use crate::LemmatizationError;
+
+/// The marker for a token without a head.
+static NO_HEAD: &str = "-";
+
+/// A token with its optional head and dependents.
+///
+/// The graph is read from the test case format without the expected
+/// output. The token is the first token after the root, the head is the
+/// second token (if any), followed by the dependents.
+#[derive(Clone)]
+pub struct TestGraph {
+    graph: TestCaseGraph,
+    index: usize,
+}
+
+impl TestGraph {
+    /// Parse a graph from a line.
+    ///
+    /// The line number is only used in errors.
+    pub fn parse(line: &str, line_number: usize) -> Result<Self, LemmatizationError> {
+        let mut iter = line.split_whitespace();
+        let (graph, index) = read_graph(&mut iter, line_number, |_| Ok(()))?;
+        Ok(TestGraph { graph, index })
+    }
+
+    /// Get the graph.
+    pub fn graph(&self) -> &dyn DependencyGraph {
+        &self.graph
+    }
+
+    /// Get the graph mutably.
+    pub fn graph_mut(&mut self) -> &mut dyn DependencyGraph {
+        &mut self.graph
+    }
+
+    /// Get the index of the token in the graph.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the token.
+    pub fn token(&self) -> &dyn Token {
+        self.graph.token(self.index)
+    }
+}
+
+/// A test case for a transformation.
+pub struct TestCase {
+    graph: TestCaseGraph,
+    index: usize,
+    correct: String,
+    line: usize,
+}
+
+impl TestCase {
+    /// Get the expected output of the transformation.
+    pub fn correct(&self) -> &str {
+        &self.correct
+    }
+
+    /// Get the graph of the test case.
+    pub fn graph(&self) -> &dyn DependencyGraph {
+        &self.graph
+    }
+
+    /// Get the line number of the test case.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Get the token that is transformed.
+    pub fn token(&self) -> &dyn Token {
+        self.graph.token(self.index)
+    }
+
+    /// Get the output of a `MiscTransform` for the token.
+    pub fn misc_transform(&self, transform: &dyn MiscTransform) -> Option<String> {
+        transform.transform(&self.graph, self.index)
+    }
+
+    /// Get the output of a `Transform` for the token.
+    pub fn transform(&self, transform: &dyn Transform) -> Cow<'_, str> {
+        transform.transform(&self.graph, self.index)
+    }
+}
+
+impl From<TestCase> for TestGraph {
+    /// Get the graph of a test case, discarding the expected output.
+    fn from(test_case: TestCase) -> Self {
+        TestGraph {
+            graph: test_case.graph,
+            index: test_case.index,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TestCaseGraph(DiGraph<SimpleToken, String>);
+
+impl DependencyGraph for TestCaseGraph {
+    fn dependents<'a>(&'a self, idx: usize) -> Box<dyn Iterator<Item = (usize, &'a str)> + 'a> {
+        Box::new(
+            self.0
+                .edges_directed(NodeIndex::new(idx), Direction::Outgoing)
+                .map(|e| (e.target().index(), e.weight().as_str())),
+        )
+    }
+
+    fn head(&self, idx: usize) -> Option<(usize, &str)> {
+        self.0
+            .edges_directed(NodeIndex::new(idx), Direction::Incoming)
+            .next()
+            .map(|e| (e.source().index(), e.weight().as_str()))
+    }
+
+    fn token(&self, idx: usize) -> &dyn Token {
+        &self.0[NodeIndex::new(idx)]
+    }
+
+    fn token_mut(&mut self, idx: usize) -> &mut dyn TokenMut {
+        &mut self.0[NodeIndex::new(idx)]
+    }
+
+    fn len(&self) -> usize {
+        self.0.node_count()
+    }
+}
+
+fn read_dependency<'a>(
+    iter: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Option<(String, SimpleToken)>, LemmatizationError> {
+    // If there is a relation, read it, otherwise bail out.
+    let rel = match iter.next() {
+        Some(rel) => rel.to_owned(),
+        None => return Ok(None),
+    };
+
+    // However, if there is a relation and no token, the test case is
+    // invalid.
+    match read_token(iter) {
+        Some(token) => Ok(Some((rel, token))),
+        None => Err(LemmatizationError::InvalidTestCase {
+            line,
+            reason: "incomplete dependency relation".to_owned(),
+        }),
+    }
+}
+
+fn read_token<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Option<SimpleToken> {
+    Some(
+        SimpleToken::new(iter.next()?)
+            .with_lemma(iter.next()?)
+            .with_upos(iter.next()?)
+            .with_xpos(iter.next()?),
+    )
+}
+
+/// Read a token, fields after the token, and the optional head and
+/// dependents.
+fn read_graph<'a, I, F>(
+    iter: &mut I,
+    line: usize,
+    mut read_fields: F,
+) -> Result<(TestCaseGraph, usize), LemmatizationError>
+where
+    I: Iterator<Item = &'a str>,
+    F: FnMut(&mut I) -> Result<(), LemmatizationError>,
+{
+    let mut graph = DiGraph::new();
+
+    graph.add_node(
+        SimpleToken::new("ROOT")
+            .with_lemma("ROOT")
+            .with_upos("root")
+            .with_xpos("root"),
+    );
+
+    let token = read_token(iter).ok_or_else(|| LemmatizationError::InvalidTestCase {
+        line,
+        reason: "incomplete token".to_owned(),
+    })?;
+    let index = graph.add_node(token);
+
+    read_fields(iter)?;
+
+    // Optional: read head
+    let mut iter = iter.peekable();
+    if iter.peek() == Some(&NO_HEAD) {
+        iter.next();
+    } else if let Some((rel, head)) = read_dependency(&mut iter, line)? {
+        let head_index = graph.add_node(head);
+        graph.add_edge(head_index, index, rel);
+    }
+
+    // Optional: read dependents
+    while let Some((rel, dep)) = read_dependency(&mut iter, line)? {
+        let dep_index = graph.add_node(dep);
+        graph.add_edge(index, dep_index, rel);
+    }
+
+    Ok((TestCaseGraph(graph), index.index()))
+}
+
+/// Read test cases from a buffered reader.
+pub fn read_test_cases<R>(buf_read: R) -> Result<Vec<TestCase>, LemmatizationError>
+where
+    R: BufRead,
+{
+    let mut test_cases = Vec::new();
+
+    for (line_idx, line) in buf_read.lines().enumerate() {
+        let line = line?;
+        let line_str = line.trim();
+        let line_number = line_idx + 1;
+
+        // Skip empty lines
+        if line_str.is_empty() {
+            continue;
+        }
+
+        // Skip comments
+        if line_str.starts_with('#') {
+            continue;
+        }
+
+        let mut iter = line.split_whitespace();
+
+        let mut correct = None;
+        let (graph, index) = read_graph(&mut iter, line_number, |iter| {
+            correct = Some(
+                iter.next()
+                    .ok_or_else(|| LemmatizationError::InvalidTestCase {
+                        line: line_number,
+                        reason: "gold standard lemma missing".to_owned(),
+                    })?,
+            );
+            Ok(())
+        })?;
+
+        test_cases.push(TestCase {
+            graph,
+            index,
+            correct: correct.unwrap().to_owned(),
+            line: line_number,
+        });
+    }
+
+    Ok(test_cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::transform::lemmatization::AddSeparatedVerbPrefix;
+    use crate::LemmatizationError;
+
+    use super::{read_test_cases, TestGraph};
+
+    #[test]
+    pub fn reads_test_cases() {
+        let data = "# Comment\n\n_ zeichnen _ VVFIN ab#zeichnen - AVZ ab _ _ PTKVZ\n";
+        let test_cases = read_test_cases(Cursor::new(data)).unwrap();
+        assert_eq!(test_cases.len(), 1);
+
+        let test_case = &test_cases[0];
+        assert_eq!(test_case.line(), 3);
+        assert_eq!(test_case.correct(), "ab#zeichnen");
+        assert_eq!(test_case.token().lemma(), "zeichnen");
+        assert_eq!(test_case.graph().len(), 3);
+        assert_eq!(test_case.graph().head(1), None);
+        assert_eq!(
+            test_case.graph().dependents(1).collect::<Vec<_>>(),
+            vec![(2, "AVZ")]
+        );
+        assert_eq!(
+            test_case.transform(&AddSeparatedVerbPrefix::new(true)),
+            "ab#zeichnen"
+        );
+    }
+
+    #[test]
+    pub fn rejects_invalid_test_cases() {
+        for (data, line) in &[
+            ("_ zeichnen _\n", 1),
+            ("\n_ zeichnen _ VVFIN\n", 2),
+            ("_ zeichnen _ VVFIN ab#zeichnen - AVZ ab\n", 1),
+        ] {
+            match read_test_cases(Cursor::new(data)) {
+                Err(LemmatizationError::InvalidTestCase { line: err_line, .. }) => {
+                    assert_eq!(err_line, *line)
+                }
+                _ => panic!("Invalid test case was accepted: {}", data),
+            }
+        }
+    }
+
+    #[test]
+    pub fn parses_test_graph() {
+        let graph = TestGraph::parse("mich ich _ PPER OBJA freue freuen _ VVFIN", 1).unwrap();
+        assert_eq!(graph.index(), 1);
+        assert_eq!(graph.token().form(), "mich");
+        assert_eq!(graph.graph().len(), 3);
+        assert_eq!(
+            graph.graph().dependents(2).collect::<Vec<_>>(),
+            vec![(1, "OBJA")]
+        );
+        assert_eq!(graph.graph().head(1), Some((2, "OBJA")));
+        assert_eq!(graph.graph().head(2), None);
+    }
+}
@@ -0,0 +1,46 @@
+//! Borrowed dependency relations of udgraph sentences.
+//!
+//! The dependency graph API of udgraph 0.8 returns `DepTriple`s, whose
+//! relations borrow from the triple rather than from the sentence. So,
+//! relations cannot be returned with the lifetime of the sentence through
+//! that API. This module reads the relations from the underlying petgraph
+//! graph instead. It relies on the graph layout of udgraph 0.8: relations
+//! are edges from the head to the dependent with the weight
+//! `(RelationType, Option<String>)`, where `RelationType::Regular` marks
+//! basic (non-enhanced) dependencies. This module is the only place that
+//! depends on this layout, and has to be checked when udgraph is updated.
+
+use petgraph::graph::{EdgeReference, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use udgraph::graph::{Edge, RelationType, Sentence};
+
+/// Get the dependents of a node with their relations.
+pub(crate) fn dependents(sentence: &Sentence, idx: usize) -> impl Iterator<Item = (usize, &str)> {
+    regular_edges(sentence, idx, Direction::Outgoing)
+        .map(|edge| (edge.target().index(), relation(edge.weight())))
+}
+
+/// Get the head of a node with the relation to the head.
+pub(crate) fn head(sentence: &Sentence, idx: usize) -> Option<(usize, &str)> {
+    regular_edges(sentence, idx, Direction::Incoming)
+        .next()
+        .map(|edge| (edge.source().index(), relation(edge.weight())))
+}
+
+fn regular_edges(
+    sentence: &Sentence,
+    idx: usize,
+    direction: Direction,
+) -> impl Iterator<Item = EdgeReference<'_, Edge>> {
+    sentence
+        .get_ref()
+        .edges_directed(NodeIndex::new(idx), direction)
+        .filter(|edge| edge.weight().0 == RelationType::Regular)
+}
+
+fn relation(edge: &Edge) -> &str {
+    edge.1
+        .as_deref()
+        .expect("Edge without a dependency relation")
+}